@@ -0,0 +1,208 @@
+use crate::lms::*;
+
+pub const ALARM_FIELDS: [&str; 5] = [
+    "Time (HH:MM)",
+    "Days (0 = Sun ... 6 = Sat)",
+    "Repeat (y/n)",
+    "Volume (0-100)",
+    "Playlist URL (blank = current)",
+];
+
+const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+pub struct AlarmForm {
+    pub id: Option<String>,
+    pub fields: Vec<String>,
+    pub focus: usize,
+}
+
+impl AlarmForm {
+    pub fn default() -> Self {
+        Self {
+            id: None,
+            fields: vec![
+                "07:00".to_string(),
+                "12345".to_string(),
+                "y".to_string(),
+                "50".to_string(),
+                String::new(),
+            ],
+            focus: 0,
+        }
+    }
+
+    pub fn from(alarm: &LmsAlarm) -> Self {
+        let days = alarm.days()
+            .iter()
+            .map(|day| day.to_string())
+            .collect::<String>();
+        let url = if alarm.plays_current_playlist() {
+            String::new()
+        } else {
+            alarm.url.clone()
+        };
+
+        Self {
+            id: Some(alarm.id.clone()),
+            fields: vec![
+                format_alarm_time(alarm.time),
+                days,
+                if alarm.repeat { "y" } else { "n" }.to_string(),
+                alarm.volume.to_string(),
+                url,
+            ],
+            focus: 0,
+        }
+    }
+
+    pub fn next_field(&mut self) {
+        self.focus = (self.focus + 1) % self.fields.len();
+    }
+
+    pub fn previous_field(&mut self) {
+        self.focus = (self.focus + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.fields[self.focus].push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.fields[self.focus].pop();
+    }
+
+    // Returns the tagged parameters for 'alarm add' or 'alarm update', or
+    // None if any of the fields can't be understood
+    pub fn params(&self) -> Option<Vec<String>> {
+        let time = parse_alarm_time(&self.fields[0])?;
+
+        let mut days: Vec<u32> = self.fields[1]
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .map(|c| c.to_digit(10).unwrap())
+            .collect();
+        days.sort();
+        days.dedup();
+        if days.is_empty() || days.iter().any(|day| *day > 6) {
+            return None;
+        }
+        let days = days
+            .iter()
+            .map(|day| day.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let repeat = match self.fields[2].trim() {
+            "y" | "Y" => 1,
+            "n" | "N" => 0,
+            _ => return None,
+        };
+
+        let volume = self.fields[3].trim().parse::<u64>().ok()?;
+        if volume > 100 {
+            return None;
+        }
+
+        // The server takes url:0 to mean the current playlist
+        let url = match self.fields[4].trim() {
+            "" => "0",
+            url => url,
+        };
+
+        let mut params = vec![
+            format!("time:{}", time),
+            format!("dow:{}", days),
+            format!("repeat:{}", repeat),
+            format!("volume:{}", volume),
+            format!("url:{}", url),
+        ];
+        match &self.id {
+            Some(id) => params.insert(0, format!("id:{}", id)),
+            None => params.push("enabled:1".to_string()),
+        }
+
+        Some(params)
+    }
+}
+
+pub fn format_alarm_time(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 3600 % 24, seconds / 60 % 60)
+}
+
+fn parse_alarm_time(text: &str) -> Option<u64> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let hours = hours.parse::<u64>().ok()?;
+    let minutes = minutes.parse::<u64>().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    Some(hours * 3600 + minutes * 60)
+}
+
+pub fn describe_days(days: &[u64]) -> String {
+    match days {
+        [0, 1, 2, 3, 4, 5, 6] => "Every day".to_string(),
+        [1, 2, 3, 4, 5] => "Weekdays".to_string(),
+        [0, 6] => "Weekends".to_string(),
+        [] => "Never".to_string(),
+        _ => days
+            .iter()
+            .map(|day| DAY_NAMES[*day as usize])
+            .collect::<Vec<&str>>()
+            .join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(url: &str) -> AlarmForm {
+        let mut form = AlarmForm::default();
+        form.fields[4] = url.to_string();
+        form
+    }
+
+    #[test]
+    fn blank_playlist_plays_the_current_one() {
+        assert_eq!(
+            form("  ").params(),
+            Some(vec![
+                "time:25200".to_string(),
+                "dow:1,2,3,4,5".to_string(),
+                "repeat:1".to_string(),
+                "volume:50".to_string(),
+                "url:0".to_string(),
+                "enabled:1".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn updates_keep_the_alarm_id_and_url() {
+        let mut form = form("file:///music/wake%20up.m3u");
+        form.id = Some("a1b2".to_string());
+        form.fields[2] = "n".to_string();
+
+        let params = form.params().unwrap();
+        assert_eq!(params[0], "id:a1b2");
+        assert_eq!(params[3], "repeat:0");
+        assert_eq!(params[5], "url:file:///music/wake%20up.m3u");
+        assert!(!params.contains(&"enabled:1".to_string()));
+    }
+
+    #[test]
+    fn rejects_fields_it_cannot_understand() {
+        let mut bad_time = AlarmForm::default();
+        bad_time.fields[0] = "24:00".to_string();
+        let mut bad_days = AlarmForm::default();
+        bad_days.fields[1] = "7".to_string();
+        let mut bad_volume = AlarmForm::default();
+        bad_volume.fields[3] = "101".to_string();
+
+        assert_eq!(bad_time.params(), None);
+        assert_eq!(bad_days.params(), None);
+        assert_eq!(bad_volume.params(), None);
+    }
+}
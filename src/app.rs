@@ -1,14 +1,24 @@
-use serde_json::{from_str, json};
-use ratatui::widgets::ListState;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::time::{Duration, Instant};
+use ratatui::{
+    layout::Rect,
+    widgets::ListState,
+};
 
+use crate::alarms::*;
+use crate::browser::*;
+use crate::cli::*;
+use crate::cometd::*;
+use crate::command::*;
 use crate::config::*;
+use crate::history::*;
 use crate::lms::*;
 
-type ReqResult<T> = Result<T, reqwest::Error>;
-type JsonValue = serde_json::Value;
 
 pub struct PlayerList {
     pub players: Vec<LmsPlayer>,
+    pub groups: Vec<LmsSyncGroup>,
     pub state: ListState,
 }
 
@@ -19,6 +29,7 @@ impl PlayerList {
 
         Self {
             players: Vec::new(),
+            groups: Vec::new(),
             state
         }
     }
@@ -26,128 +37,1326 @@ impl PlayerList {
     pub fn is_empty(&self) -> bool {
         self.players.is_empty()
     }
+
+    // Groups are numbered in the order the server lists them, which is
+    // enough to tell which players belong together
+    pub fn group_number(&self, playerid: &str) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.contains(playerid))
+            .map(|index| index + 1)
+    }
+}
+
+pub struct PlayerCard {
+    pub status: LmsStatus,
+    pub track: Option<LmsSong>,
+}
+
+#[derive(Clone, Copy)]
+pub enum AppState {
+    PlayerMenu,
+    Dashboard,
+    Playlist,
+    Library,
+    Search,
+    SavedPlaylists,
+    Favorites,
+    Apps,
+    MusicFolder,
+    Alarms,
+}
+
+pub struct SearchPrompt {
+    pub text: String,
+    pub editing: bool,
+    last_edit: Option<Instant>,
+}
+
+impl SearchPrompt {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            editing: false,
+            last_edit: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.last_edit.is_some()
+    }
+}
+
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+// How long a lone digit waits for a key that takes a count before it seeks
+const COUNT_TIMEOUT: Duration = Duration::from_secs(1);
+const TRANSPORT_RETRY: Duration = Duration::from_secs(30);
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+
+pub struct Disconnected {
+    pub error: String,
+    pub attempts: u32,
+    retry_at: Instant,
+}
+
+impl Disconnected {
+    pub fn retry_in(&self) -> Duration {
+        self.retry_at.saturating_duration_since(Instant::now())
+    }
+}
+
+pub struct PlayerTarget {
+    pub name: String,
+    pub playerid: String,
+}
+
+#[derive(Clone, Copy)]
+pub enum SleepTimer {
+    Minutes(u64),
+    EndOfTrack,
+    Cancel,
 }
 
-pub enum AppState {
-    PlayerMenu,
-    Playlist
-}
+impl SleepTimer {
+    pub fn options() -> Vec<Self> {
+        vec![
+            SleepTimer::Minutes(15),
+            SleepTimer::Minutes(30),
+            SleepTimer::Minutes(60),
+            SleepTimer::Minutes(90),
+            SleepTimer::EndOfTrack,
+            SleepTimer::Cancel,
+        ]
+    }
+}
+
+impl fmt::Display for SleepTimer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SleepTimer::Minutes(minutes) => write!(f, "{} minutes", minutes),
+            SleepTimer::EndOfTrack => write!(f, "End of current track"),
+            SleepTimer::Cancel => write!(f, "Cancel sleep timer"),
+        }
+    }
+}
+
+pub enum Popup {
+    JumpToTrack(String),
+    ConfirmClear,
+    SavePlaylist(String),
+    ConfirmDeletePlaylist { id: String, name: String },
+    ConfirmDeleteFavorite { id: String, name: String },
+    BrowseInput { title: String, query: BrowseQuery, text: String },
+    SyncWith { playerid: String, targets: Vec<PlayerTarget>, selected: usize },
+    TransferTo { targets: Vec<PlayerTarget>, selected: usize },
+    SleepTimer(usize),
+    AlarmForm(AlarmForm),
+    ConfirmDeleteAlarm { id: String, time: String },
+}
+
+pub struct App {
+    client: LmsClient,
+    pub state: AppState,
+    pub popup: Option<Popup>,
+    pub error: Option<LmsError>,
+    pub disconnected: Option<Disconnected>,
+    pub quit: bool,
+    pub player: Option<LmsPlayer>,
+    pub playlist: Option<LmsPlaylist>,
+    pub status: Option<LmsStatus>,
+    pub playlist_state: ListState,
+    pub playlist_page_size: usize,
+    pub count: Option<usize>,
+    count_typed: Option<Instant>,
+    pub follow_playing: bool,
+    last_scroll: Option<Instant>,
+    history: PlaylistHistory,
+    playlist_timestamp: Option<f64>,
+    expecting_playlist_change: bool,
+    pub player_list: PlayerList,
+    pub dashboard: Vec<Option<PlayerCard>>,
+    pub dashboard_columns: usize,
+    pub browser: Browser,
+    pub browser_page_size: usize,
+    pub alarms: Vec<LmsAlarm>,
+    pub alarms_state: ListState,
+    pub alarms_enabled: bool,
+    pub search: SearchPrompt,
+    search_return: Option<(AppState, Browser)>,
+    pub playbar_area: Option<Rect>,
+    listener: Option<CliListener>,
+    subscription: Option<CometdSubscription>,
+    transport_retry: Option<Instant>,
+    stale: bool,
+    last_tick: Instant,
+    pub config: Config,
+}
+
+impl App {
+    pub fn from(config: Config) -> Self {
+        let client = LmsClient::from(
+            format!("{}:{}", config.lms_ip, config.lms_port)
+        );
+
+        Self {
+            client,
+            state: AppState::PlayerMenu,
+            popup: None,
+            error: None,
+            disconnected: None,
+            quit: false,
+            player: None,
+            playlist: None,
+            status: None,
+            playlist_state: ListState::default(),
+            playlist_page_size: 1,
+            count: None,
+            count_typed: None,
+            follow_playing: true,
+            last_scroll: None,
+            history: PlaylistHistory::default(),
+            playlist_timestamp: None,
+            expecting_playlist_change: false,
+            player_list: PlayerList::default(),
+            dashboard: Vec::new(),
+            dashboard_columns: 1,
+            browser: Browser::from("Library", library_menu()),
+            browser_page_size: 1,
+            alarms: Vec::new(),
+            alarms_state: ListState::default(),
+            alarms_enabled: false,
+            search: SearchPrompt::default(),
+            search_return: None,
+            playbar_area: None,
+            listener: None,
+            subscription: None,
+            transport_retry: None,
+            stale: true,
+            last_tick: Instant::now(),
+            config
+        }
+    }
+
+    pub async fn on_tick(&mut self) -> LmsResult<()> {
+        let elapsed = self.last_tick.elapsed().as_secs_f64();
+        self.last_tick = Instant::now();
+
+        if let Some(disconnected) = &self.disconnected {
+            if disconnected.retry_in() > Duration::ZERO {
+                return Ok(());
+            }
+            self.reconnect().await?;
+        }
+
+        let refresh = match self.config.transport {
+            Transport::HTTP => true,
+            Transport::CLI => self.check_notifications().await,
+            Transport::COMETD => self.check_subscription().await,
+        };
+        if !refresh {
+            self.advance_clock(elapsed);
+            return Ok(());
+        }
+
+        match self.state {
+            AppState::PlayerMenu => self.update_player_list().await?,
+            AppState::Dashboard => {
+                self.update_player_list().await?;
+                self.update_dashboard().await?;
+            },
+            AppState::Playlist => self.update_playlist_info().await?,
+            AppState::Library => {},
+            AppState::Search => {},
+            AppState::SavedPlaylists => {},
+            AppState::Favorites => {},
+            AppState::Apps => {},
+            AppState::MusicFolder => {},
+            AppState::Alarms => {},
+        }
+
+        Ok(())
+    }
+
+    // Returns whether anything on screen might have changed since the last
+    // tick. Without a CLI connection to tell us, we have to assume it has
+    async fn check_notifications(&mut self) -> bool {
+        let listener = match self.listener.as_mut() {
+            Some(listener) => listener,
+            None => {
+                self.connect_listener().await;
+                return true;
+            },
+        };
+
+        let events = listener.events();
+        if events.contains(&CliEvent::Disconnected) {
+            self.listener = None;
+            self.transport_retry = Some(Instant::now());
+            return true;
+        }
+
+        let stale = self.stale;
+        self.stale = false;
+
+        let relevant = match self.get_current_playerid() {
+            Some(playerid) if matches!(self.state, AppState::Playlist) => {
+                events.iter().any(|event| event.concerns(&playerid))
+            },
+            _ => !events.is_empty(),
+        };
+
+        stale || relevant
+    }
+
+    async fn connect_listener(&mut self) {
+        if self.transport_retry.is_some_and(|t| t.elapsed() < TRANSPORT_RETRY) {
+            return;
+        }
+
+        let address = format!("{}:{}", self.config.lms_ip, self.config.cli_port);
+        match CliListener::connect(&address).await {
+            Ok(listener) => {
+                self.listener = Some(listener);
+                self.transport_retry = None;
+            },
+            Err(_) => self.transport_retry = Some(Instant::now()),
+        }
+    }
+
+    // The subscription only covers the player being viewed, so the other
+    // views keep polling. Returns whether we still need to poll
+    async fn check_subscription(&mut self) -> bool {
+        let playerid = match self.get_current_playerid() {
+            Some(playerid) if matches!(self.state, AppState::Playlist) => {
+                playerid
+            },
+            _ => return true,
+        };
+
+        let subscription = match self.subscription.as_mut() {
+            Some(subscription) if subscription.playerid == playerid => {
+                subscription
+            },
+            _ => {
+                self.subscribe(&playerid).await;
+                return true;
+            },
+        };
+
+        for event in subscription.events() {
+            match event {
+                CometdEvent::Status(status) => {
+                    self.check_playlist_timestamp(status.playlist_timestamp);
+                    self.status = Some(status);
+                },
+                // Playlists older than ours were queued up before our own
+                // edits were refreshed over HTTP
+                CometdEvent::Playlist { playlist, timestamp } => {
+                    let stale = matches!(
+                        (timestamp, self.playlist_timestamp),
+                        (Some(timestamp), Some(known)) if timestamp < known
+                    );
+                    if !stale {
+                        self.playlist = Some(playlist);
+                    }
+                },
+                CometdEvent::Disconnected => {
+                    self.subscription = None;
+                    self.transport_retry = Some(Instant::now());
+                    return true;
+                },
+            }
+        }
+
+        false
+    }
+
+    async fn subscribe(&mut self, playerid: &str) {
+        self.subscription = None;
+        if self.transport_retry.is_some_and(|t| t.elapsed() < TRANSPORT_RETRY) {
+            return;
+        }
+
+        match CometdSubscription::subscribe(self.client.clone(), playerid).await {
+            Ok(subscription) => {
+                self.subscription = Some(subscription);
+                self.transport_retry = None;
+            },
+            Err(_) => self.transport_retry = Some(Instant::now()),
+        }
+    }
+
+    // Between notifications nothing changes except the clock, so we keep
+    // it ticking here instead of asking the server for it
+    fn advance_clock(&mut self, elapsed: f64) {
+        let statuses = self.status
+            .iter_mut()
+            .chain(self.dashboard.iter_mut().flatten().map(|c| &mut c.status));
+        for status in statuses {
+            if status.playlist_mode == PlaylistMode::PLAY {
+                status.elapsed_duration += elapsed;
+            }
+            if let Some(sleep) = status.will_sleep_in {
+                status.will_sleep_in = Some((sleep - elapsed).max(0.0));
+            }
+        }
+
+        if let AppState::Playlist = self.state {
+            self.update_state();
+        }
+    }
+
+    async fn update_player_list(&mut self) -> LmsResult<()> {
+        let res: ServerStatusResponse = self.client
+            .query("-", &LmsCommand::ServerStatus)
+            .await?;
+        let player_list = res.players;
+        let res: SyncGroupsResponse = self.client
+            .query("-", &LmsCommand::SyncGroups)
+            .await?;
+        self.player_list.groups = res.groups;
+
+        // Players can drop off between ticks, so keep the cursor on the list
+        self.player_list.players = player_list.clone();
+        if player_list.is_empty() {
+            self.player_list.state.select(None);
+        } else if let Some(index) = self.player_list.state.selected() {
+            let last = player_list.len() - 1;
+            self.player_list.state.select(Some(index.min(last)));
+        }
+
+        Ok(())
+    }
+
+    async fn update_dashboard(&mut self) -> LmsResult<()> {
+        let mut dashboard = Vec::new();
+        for player in self.player_list.players.iter() {
+            let command = LmsCommand::Status(
+                StatusQuery::current().tags("adl")
+            );
+            let res: StatusResponse = self.client
+                .query(&player.playerid, &command)
+                .await?;
+
+            // One player reporting something odd shouldn't take the others
+            // down with it
+            let track = res.tracks.first().cloned();
+            let card = LmsStatus::from_response(res)
+                .ok()
+                .map(|status| PlayerCard { status, track });
+
+            dashboard.push(card);
+        }
+
+        self.dashboard = dashboard;
+
+        Ok(())
+    }
+
+    async fn update_playlist_info(&mut self) -> LmsResult<()> {
+        self.get_current_status().await?;
+        self.get_current_playlist().await?;
+        self.update_state();
+
+        Ok(())
+    }
+
+    pub async fn select_player(&mut self) -> LmsResult<()> {
+        let player = self.player_list.state
+            .selected()
+            .and_then(|index| self.player_list.players.get(index))
+            .cloned();
+        if let Some(player) = player {
+            self.view_player(player).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn view_player(&mut self, player: LmsPlayer) -> LmsResult<()> {
+        self.player = Some(player);
+        self.history.clear();
+        self.playlist_timestamp = None;
+        self.follow_playing = true;
+        self.update_playlist_info().await
+    }
+
+    pub async fn open_transfer_popup(&mut self) -> LmsResult<()> {
+        self.update_player_list().await?;
+
+        // Players synced with this one share its playlist, so clearing it
+        // on them would clear ours too
+        let mut excluded = self.status
+            .as_ref()
+            .map(|status| status.sync_members())
+            .unwrap_or_default();
+        excluded.extend(self.get_current_playerid());
+        let targets = self.player_list.players
+            .iter()
+            .filter(|player| !excluded.contains(&player.playerid))
+            .map(|player| PlayerTarget {
+                name: player.name.clone(),
+                playerid: player.playerid.clone(),
+            })
+            .collect();
+
+        self.popup = Some(Popup::TransferTo { targets, selected: 0 });
+
+        Ok(())
+    }
+
+    pub async fn transfer_queue(
+        &mut self,
+        target: &str,
+        stop_source: bool
+    ) -> LmsResult<()> {
+        let (status, playlist) = match (&self.status, &self.playlist) {
+            (Some(status), Some(playlist)) => (status, playlist),
+            _ => return Ok(()),
+        };
+
+        // Shuffle is switched off on the target, since our copy of the
+        // playlist is already in the order it is being played in
+        let mut commands = vec![
+            LmsCommand::Power(true),
+            LmsCommand::Playlist(PlaylistCommand::Clear),
+            LmsCommand::Playlist(PlaylistCommand::Shuffle(ShuffleMode::NONE)),
+            LmsCommand::Playlist(
+                PlaylistCommand::Repeat(status.playlist_repeat.clone())
+            ),
+        ];
+        commands.append(&mut queue_commands(&playlist.tracks));
+
+        if status.total_tracks != 0 {
+            // Tracks that couldn't be added shift the current one up
+            let index = status.playlist_index as usize;
+            let skipped = playlist.tracks
+                .iter()
+                .take(index)
+                .filter(|track| !track.can_be_added())
+                .count();
+            commands.push(LmsCommand::Playlist(
+                PlaylistCommand::Jump(index - skipped)
+            ));
+            let seekable = playlist.tracks
+                .get(index)
+                .is_some_and(|track| {
+                    track.can_be_added() && track.is_seekable()
+                });
+            if seekable && status.elapsed_duration > 0.0 {
+                commands.push(
+                    LmsCommand::Time(Seek::To(status.elapsed_duration))
+                );
+            }
+        }
+
+        for command in commands {
+            self.client.request(target, &command).await?;
+        }
+
+        if stop_source {
+            self.stop().await?;
+
+            // The music moved, so we follow it to the other player
+            let player = self.player_list.players
+                .iter()
+                .find(|player| player.playerid == target)
+                .cloned();
+            if let Some(player) = player {
+                self.view_player(player).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn open_dashboard(&mut self) -> LmsResult<()> {
+        self.update_dashboard().await?;
+        self.change_state(AppState::Dashboard);
+
+        Ok(())
+    }
+
+    // Something odd coming back from the server shouldn't end the session,
+    // so errors are kept around to be shown until the next key press. Not
+    // hearing back at all, or the server failing on its end, is handled by
+    // waiting for the server instead
+    pub fn report(&mut self, res: LmsResult<()>) {
+        match res {
+            Ok(()) => {},
+            Err(err @ LmsError::Transport(_)) => self.disconnect(err),
+            Err(err @ LmsError::HttpStatus(status))
+                if status.is_server_error() => self.disconnect(err),
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    fn disconnect(&mut self, err: LmsError) {
+        let attempts = self.disconnected
+            .as_ref()
+            .map_or(0, |disconnected| disconnected.attempts + 1);
+        let delay = reconnect_delay(attempts);
+
+        self.disconnected = Some(Disconnected {
+            error: err.to_string(),
+            attempts,
+            retry_at: Instant::now() + delay,
+        });
+        self.error = None;
+
+        // Whatever we were listening to went down with the server
+        self.listener = None;
+        self.subscription = None;
+        self.transport_retry = None;
+    }
+
+    async fn reconnect(&mut self) -> LmsResult<()> {
+        // The player list is cheap to ask for, and once it comes back the
+        // view and player we left off on simply pick up where they were
+        self.update_player_list().await?;
+        self.disconnected = None;
+        self.stale = true;
+
+        Ok(())
+    }
+
+    pub fn retry_now(&mut self) {
+        if let Some(disconnected) = self.disconnected.as_mut() {
+            disconnected.retry_at = Instant::now();
+        }
+    }
+
+    pub fn server_address(&self) -> String {
+        format!("{}:{}", self.config.lms_ip, self.config.lms_port)
+    }
+
+    pub fn change_state(&mut self, new_state: AppState) {
+        self.state = new_state;
+        // Notifications that arrived while we were elsewhere were ignored
+        self.stale = true;
+    }
+
+    async fn player_query<T: DeserializeOwned>(
+        &self,
+        command: LmsCommand
+    ) -> LmsResult<T> {
+        let playerid = self.get_current_playerid()
+            .unwrap_or_else(|| "-".to_string());
+
+        self.client.query(&playerid, &command).await
+    }
+
+    async fn player_command(&self, command: LmsCommand) -> LmsResult<()> {
+        if let Some(playerid) = self.get_current_playerid() {
+            self.client.request(&playerid, &command).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn toggle_pause(&self) -> LmsResult<()> {
+        let stopped = self.status
+            .as_ref()
+            .is_none_or(|s| s.playlist_mode == PlaylistMode::STOP);
+
+        if stopped {
+            self.player_command(LmsCommand::Play).await
+        } else {
+            self.player_command(LmsCommand::Pause).await
+        }
+    }
+
+    pub async fn stop(&self) -> LmsResult<()> {
+        self.player_command(LmsCommand::Stop).await
+    }
+
+    pub async fn next_track(&self) -> LmsResult<()> {
+        self.player_command(LmsCommand::Playlist(PlaylistCommand::Skip(1))).await
+    }
+
+    pub async fn previous_track(&self) -> LmsResult<()> {
+        self.player_command(LmsCommand::Playlist(PlaylistCommand::Skip(-1))).await
+    }
+
+    pub async fn restart_track(&self) -> LmsResult<()> {
+        self.player_command(LmsCommand::Time(Seek::To(0.0))).await
+    }
+
+    pub async fn change_volume(&self, up: bool) -> LmsResult<()> {
+        let step = self.config.volume_step as i64;
+        let delta = if up { step } else { -step };
+
+        self.player_command(LmsCommand::Volume(delta)).await
+    }
+
+    pub async fn toggle_mute(&self) -> LmsResult<()> {
+        self.player_command(LmsCommand::ToggleMute).await
+    }
+
+    pub async fn toggle_power(&mut self) -> LmsResult<()> {
+        if self.player.as_ref().is_some_and(|player| !player.canpoweroff) {
+            return Ok(());
+        }
+
+        if let Some(status) = &self.status {
+            self.player_command(LmsCommand::Power(!status.power)).await?;
+            self.update_playlist_info().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_sleep_timer(&mut self, timer: SleepTimer) -> LmsResult<()> {
+        let seconds = match timer {
+            SleepTimer::Minutes(minutes) => minutes * 60,
+            SleepTimer::Cancel => 0,
+            SleepTimer::EndOfTrack => {
+                // Streams never end, so there is nothing to wait for
+                let elapsed = self.status
+                    .as_ref()
+                    .map_or(0.0, |status| status.elapsed_duration);
+                match self.current_track() {
+                    Some(track) if track.is_seekable() => {
+                        (track.duration - elapsed).max(1.0).ceil() as u64
+                    },
+                    _ => return Ok(()),
+                }
+            },
+        };
+
+        self.player_command(LmsCommand::Sleep(seconds)).await?;
+        self.update_playlist_info().await
+    }
+
+    pub async fn cycle_repeat(&self) -> LmsResult<()> {
+        if let Some(status) = &self.status {
+            let mode = status.playlist_repeat.next();
+            self.player_command(
+                LmsCommand::Playlist(PlaylistCommand::Repeat(mode))
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn cycle_shuffle(&mut self) -> LmsResult<()> {
+        if let Some(status) = &self.status {
+            let mode = status.playlist_shuffle.next();
+            self.player_command(
+                LmsCommand::Playlist(PlaylistCommand::Shuffle(mode))
+            ).await?;
+
+            // Shuffling reorders the playlist, so show it straight away
+            self.update_playlist_info().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn play_track(&mut self, index: usize) -> LmsResult<()> {
+        if index < self.playlist_len() {
+            self.player_command(
+                LmsCommand::Playlist(PlaylistCommand::Jump(index))
+            ).await?;
+            self.follow_playing = true;
+            self.update_playlist_info().await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn push_count_digit(&mut self, digit: usize) {
+        let count = self.count.unwrap_or(0);
+        self.count = Some(count.saturating_mul(10).saturating_add(digit));
+        self.count_typed = Some(Instant::now());
+    }
+
+    pub fn take_count(&mut self) -> Option<usize> {
+        self.count_typed = None;
+        self.count.take()
+    }
+
+    pub fn count_is_pending(&self) -> bool {
+        self.count.is_some()
+    }
+
+    pub async fn run_pending_count(&mut self) -> LmsResult<()> {
+        let due = self.count_typed
+            .is_some_and(|t| t.elapsed() >= COUNT_TIMEOUT);
+
+        if due {
+            if let Some(count) = self.take_count() {
+                self.seek_to_tenth(count).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // A lone digit that nothing picked up as a count seeks to that tenth of
+    // the track, so 5 on its own still jumps to the middle
+    pub async fn seek_to_tenth(&self, count: usize) -> LmsResult<()> {
+        if count < 10 && !self.config.read_only {
+            self.seek_to_fraction(count as f64 / 10.0).await?;
+        }
+
+        Ok(())
+    }
+
+    // Track numbers start at 1, like the ones shown in the playlist
+    pub async fn go_to_track(&mut self, number: usize) -> LmsResult<()> {
+        if number == 0 || number > self.playlist_len() {
+            return Ok(());
+        }
+
+        if self.config.read_only {
+            self.set_playlist_cursor(number - 1);
+            Ok(())
+        } else {
+            self.play_track(number - 1).await
+        }
+    }
+
+    pub async fn play_selected_track(&mut self) -> LmsResult<()> {
+        if let Some(index) = self.playlist_state.selected() {
+            self.play_track(index).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run_playlist_commands(
+        &mut self,
+        commands: Vec<LmsCommand>
+    ) -> LmsResult<()> {
+        for command in commands {
+            self.player_command(command).await?;
+        }
+        self.expecting_playlist_change = true;
+
+        self.update_playlist_info().await
+    }
+
+    async fn perform_edit(&mut self, edit: PlaylistEdit) -> LmsResult<()> {
+        self.run_playlist_commands(edit.commands()).await?;
+        self.history.record(edit);
+
+        Ok(())
+    }
+
+    pub async fn undo_edit(&mut self) -> LmsResult<()> {
+        if let Some(edit) = self.history.undo() {
+            let commands = edit.inverse_commands(self.playlist_len());
+            self.run_playlist_commands(commands).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn redo_edit(&mut self) -> LmsResult<()> {
+        if let Some(edit) = self.history.redo() {
+            self.run_playlist_commands(edit.commands()).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_selected_track(&mut self) -> LmsResult<()> {
+        let track = self.playlist_state
+            .selected()
+            .and_then(|i| self.playlist.as_ref()?.tracks.get(i))
+            .cloned();
+
+        if let (Some(index), Some(track)) =
+            (self.playlist_state.selected(), track)
+        {
+            self.perform_edit(PlaylistEdit::Delete { track, index }).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn move_selected_track(&mut self, down: bool) -> LmsResult<()> {
+        if let Some(from) = self.playlist_state.selected() {
+            let len = self.playlist_len();
+            let to = if down {
+                from + 1
+            } else {
+                from.wrapping_sub(1)
+            };
+
+            if from < len && to < len {
+                self.set_playlist_cursor(to);
+                self.perform_edit(PlaylistEdit::Move { from, to }).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn clear_playlist(&mut self) -> LmsResult<()> {
+        let tracks = self.playlist
+            .as_ref()
+            .map(|p| p.tracks.clone())
+            .unwrap_or_default();
+
+        self.perform_edit(PlaylistEdit::Clear { tracks }).await
+    }
+
+    async fn open_browser(
+        &mut self,
+        state: AppState,
+        title: &str,
+        query: BrowseQuery
+    ) -> LmsResult<()> {
+        self.browser = Browser::from(title, query);
+        self.change_state(state);
+
+        self.load_browser_page().await
+    }
+
+    pub async fn open_library(&mut self) -> LmsResult<()> {
+        self.open_browser(AppState::Library, "Library", library_menu()).await
+    }
+
+    pub async fn open_saved_playlists(&mut self) -> LmsResult<()> {
+        self.open_browser(
+            AppState::SavedPlaylists,
+            "Saved Playlists",
+            BrowseQuery::Playlists
+        ).await
+    }
+
+    pub async fn open_favorites(&mut self) -> LmsResult<()> {
+        self.open_browser(
+            AppState::Favorites,
+            "Favorites",
+            favorites_menu()
+        ).await
+    }
+
+    pub async fn open_apps(&mut self) -> LmsResult<()> {
+        self.open_browser(AppState::Apps, "Radio & Apps", apps_menu()).await
+    }
+
+    pub async fn open_music_folder(&mut self) -> LmsResult<()> {
+        self.open_browser(
+            AppState::MusicFolder,
+            "Music Folder",
+            BrowseQuery::MusicFolder(None)
+        ).await
+    }
+
+    pub async fn open_alarms(&mut self) -> LmsResult<()> {
+        self.alarms_state.select(Some(0));
+        self.load_alarms().await?;
+        self.change_state(AppState::Alarms);
+
+        Ok(())
+    }
+
+    async fn load_alarms(&mut self) -> LmsResult<()> {
+        let res: AlarmsResponse = self.player_query(LmsCommand::Alarms).await?;
+        self.alarms = res.alarms;
+
+        let res: PlayerPrefResponse = self.player_query(
+            LmsCommand::PlayerPref("alarmsEnabled".to_string())
+        ).await?;
+        self.alarms_enabled = res.value == "1";
+
+        // Keep the cursor on the list as alarms come and go
+        if self.alarms.is_empty() {
+            self.alarms_state.select(None);
+        } else {
+            let index = self.alarms_state.selected().unwrap_or(0);
+            self.alarms_state.select(Some(index.min(self.alarms.len() - 1)));
+        }
+
+        Ok(())
+    }
+
+    pub fn selected_alarm(&self) -> Option<&LmsAlarm> {
+        self.alarms_state
+            .selected()
+            .and_then(|index| self.alarms.get(index))
+    }
+
+    pub async fn save_alarm(&mut self, form: &AlarmForm) -> LmsResult<()> {
+        if let Some(params) = form.params() {
+            let command = if form.id.is_some() {
+                AlarmCommand::Update(params)
+            } else {
+                AlarmCommand::Add(params)
+            };
+
+            self.player_command(LmsCommand::Alarm(command)).await?;
+            self.load_alarms().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_alarm(&mut self, id: &str) -> LmsResult<()> {
+        self.player_command(
+            LmsCommand::Alarm(AlarmCommand::Delete(id.to_string()))
+        ).await?;
+
+        self.load_alarms().await
+    }
+
+    pub async fn toggle_selected_alarm(&mut self) -> LmsResult<()> {
+        if let Some(alarm) = self.selected_alarm() {
+            self.player_command(LmsCommand::Alarm(AlarmCommand::Enable {
+                id: alarm.id.clone(),
+                enabled: !alarm.enabled,
+            })).await?;
+            self.load_alarms().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn toggle_all_alarms(&mut self) -> LmsResult<()> {
+        let command = if self.alarms_enabled {
+            AlarmCommand::DisableAll
+        } else {
+            AlarmCommand::EnableAll
+        };
+        self.player_command(LmsCommand::Alarm(command)).await?;
 
-pub struct App {
-    client: LmsClient,
-    pub state: AppState,
-    pub quit: bool,
-    pub player: Option<LmsPlayer>,
-    pub playlist: Option<LmsPlaylist>,
-    pub status: Option<LmsStatus>,
-    pub playlist_state: ListState,
-    pub player_list: PlayerList,
-    pub config: Config,
-}
+        self.load_alarms().await
+    }
 
-impl App {
-    pub fn from(config: Config) -> Self {
-        let client = LmsClient::from(
-            format!("{}:{}", config.lms_ip, config.lms_port)
-        );
+    pub fn move_alarms_cursor(&mut self, offset: isize) {
+        if let Some(index) = self.alarms_state.selected() {
+            let index = (index as isize + offset)
+                .clamp(0, self.alarms.len() as isize - 1);
+            self.alarms_state.select(Some(index as usize));
+        }
+    }
 
-        Self {
-            client,
-            state: AppState::PlayerMenu,
-            quit: false,
-            player: None,
-            playlist: None,
-            status: None,
-            playlist_state: ListState::default(),
-            player_list: PlayerList::default(),
-            config
+    pub fn jump_to_alarms_top(&mut self) {
+        if self.alarms_state.selected().is_some() {
+            self.alarms_state.select(Some(0));
         }
     }
 
-    pub async fn on_tick(&mut self) -> ReqResult<()> {
-        match self.state {
-            AppState::PlayerMenu => self.update_player_list().await?,
-            AppState::Playlist => self.update_playlist_info().await?,
+    pub fn jump_to_alarms_bottom(&mut self) {
+        if self.alarms_state.selected().is_some() {
+            self.alarms_state.select(Some(self.alarms.len() - 1));
+        }
+    }
+
+    pub async fn add_current_track_to_favorites(&self) -> LmsResult<()> {
+        if let Some(track) = self.current_track() {
+            if !track.url.is_empty() {
+                self.player_command(LmsCommand::AddFavorite {
+                    url: track.url.clone(),
+                    title: track.title.clone(),
+                }).await?;
+            }
         }
 
         Ok(())
     }
 
-    async fn update_player_list(&mut self) -> ReqResult<()> {
-        let command = json!(["-", ["serverstatus", 0]]);
-        let res = self.query(command).await?;
+    pub async fn delete_favorite(&mut self, id: &str) -> LmsResult<()> {
+        self.player_command(
+            LmsCommand::DeleteFavorite(id.to_string())
+        ).await?;
 
-        let players = res.get_array("players_loop")
-            .expect("Could not extract value");
-        let player_list: Vec<LmsPlayer> = players
-            .iter()
-            .map(|p| {
-                from_str(&p.to_string()).unwrap()
-            })
-            .collect();
+        self.reload_browser().await
+    }
 
-        self.player_list.players = player_list.clone();
-        if player_list.is_empty() {
-            self.player_list.state.select(None);
+    pub async fn reload_browser(&mut self) -> LmsResult<()> {
+        if let Some(level) = self.browser.current_mut() {
+            level.reset();
+        }
+
+        self.load_browser_page().await
+    }
+
+    pub async fn save_playlist(&mut self, name: &str) -> LmsResult<()> {
+        let name = name.trim();
+        if !name.is_empty() {
+            self.player_command(
+                LmsCommand::Playlist(PlaylistCommand::Save(name.to_string()))
+            ).await?;
+        }
+
+        if let AppState::SavedPlaylists = self.state {
+            self.reload_browser().await?;
         }
 
         Ok(())
     }
 
-    async fn update_playlist_info(&mut self) -> ReqResult<()> {
-        self.get_current_status().await?;
-        self.get_current_playlist().await?;
-        self.update_state();
+    pub async fn delete_saved_playlist(&mut self, id: &str) -> LmsResult<()> {
+        self.player_command(
+            LmsCommand::DeletePlaylist(id.to_string())
+        ).await?;
+
+        self.reload_browser().await
+    }
+
+    async fn load_browser_page(&mut self) -> LmsResult<()> {
+        let (query, start) = match self.browser.current() {
+            Some(level) if level.needs_more() => {
+                (level.query.clone(), level.items.len())
+            },
+            _ => return Ok(()),
+        };
+
+        let (items, total) = match query.command(start) {
+            Some(command) => {
+                let res: BrowseResponse = self.player_query(command).await?;
+                query.parse(res)
+            },
+            None => {
+                let items = query.static_items().unwrap_or_default();
+                let total = items.len();
+                (items, total)
+            },
+        };
+
+        if let Some(level) = self.browser.current_mut() {
+            level.append_page(items, total);
+        }
 
         Ok(())
     }
 
-    pub async fn select_player(&mut self) -> ReqResult<()> {
-        let list = &self.player_list.players;
-        if let Some(index) = self.player_list.state.selected() {
-            self.player = Some(list[index].clone());
-            self.update_playlist_info().await?;
+    pub async fn move_browser_cursor(&mut self, offset: isize) -> LmsResult<()> {
+        if let Some(level) = self.browser.current_mut() {
+            level.move_cursor(offset);
+        }
+
+        self.load_browser_page().await
+    }
+
+    pub async fn browser_page_down(&mut self) -> LmsResult<()> {
+        self.move_browser_cursor(self.browser_page_size as isize).await
+    }
+
+    pub async fn browser_page_up(&mut self) -> LmsResult<()> {
+        self.move_browser_cursor(-(self.browser_page_size as isize)).await
+    }
+
+    pub fn jump_to_browser_top(&mut self) {
+        if let Some(level) = self.browser.current_mut() {
+            level.jump_to_top();
+        }
+    }
+
+    pub async fn jump_to_browser_bottom(&mut self) -> LmsResult<()> {
+        // Only jumps to the end of what has been loaded so far, which also
+        // pulls in the next page, since huge libraries take a while to fetch
+        if let Some(level) = self.browser.current_mut() {
+            level.jump_to_bottom();
+        }
+
+        self.load_browser_page().await
+    }
+
+    pub async fn browser_enter(&mut self) -> LmsResult<()> {
+        if let Some(item) = self.browser.selected_item().cloned() {
+            if let Some(query) = item.browse {
+                if item.input {
+                    self.popup = Some(Popup::BrowseInput {
+                        title: item.name,
+                        query,
+                        text: String::new(),
+                    });
+                } else {
+                    self.browser.enter(item.name, query);
+                    self.load_browser_page().await?;
+                }
+            } else if !self.config.read_only {
+                self.play_browser_item(PlayAction::Play).await?;
+            }
         }
 
         Ok(())
     }
 
-    pub fn change_state(&mut self, new_state: AppState) {
-        self.state = new_state;
+    pub async fn browse_with_input(
+        &mut self,
+        title: &str,
+        query: &BrowseQuery,
+        text: &str
+    ) -> LmsResult<()> {
+        let text = text.trim();
+        if !text.is_empty() {
+            let title = format!("{}: {}", title, text);
+            self.browser.enter(title, query.with_search(text));
+            self.load_browser_page().await?;
+        }
+
+        Ok(())
     }
 
-    async fn query(&self, command: JsonValue) -> ReqResult<LmsResponse> {
-        self.client.query(command).await?
-            .json::<LmsResponse>().await
+    pub async fn browser_back(&mut self) -> LmsResult<()> {
+        if !self.browser.back() {
+            match self.state {
+                AppState::Search => self.close_search().await?,
+                _ => {
+                    self.change_state(AppState::Playlist);
+                    self.update_playlist_info().await?;
+                },
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn get_current_playerid(&self) -> Option<String> {
-        if let Some(player) = &self.player {
-            Some(player.playerid.clone())
+    pub fn open_search(&mut self) {
+        let results = Browser::from("Search", BrowseQuery::Search(String::new()));
+        let previous = std::mem::replace(&mut self.browser, results);
+        if let Some(level) = self.browser.current_mut() {
+            level.loaded = true;
+        }
+
+        // Keep whatever we were browsing so closing the search restores it
+        if !matches!(self.state, AppState::Search) {
+            self.search_return = Some((self.state, previous));
+        }
+
+        self.search = SearchPrompt::default();
+        self.search.editing = true;
+        self.change_state(AppState::Search);
+    }
+
+    pub async fn close_search(&mut self) -> LmsResult<()> {
+        self.search = SearchPrompt::default();
+
+        match self.search_return.take() {
+            Some((state, browser)) => {
+                self.browser = browser;
+                self.change_state(state);
+            },
+            None => self.change_state(AppState::Playlist),
+        }
+
+        self.on_tick().await
+    }
+
+    pub fn edit_search(&mut self, c: Option<char>) {
+        match c {
+            Some(c) => self.search.text.push(c),
+            None => {
+                self.search.text.pop();
+            },
+        }
+
+        self.search.last_edit = Some(Instant::now());
+    }
+
+    pub async fn submit_search(&mut self) -> LmsResult<()> {
+        self.search.editing = false;
+        self.run_search().await
+    }
+
+    pub async fn run_pending_search(&mut self) -> LmsResult<()> {
+        let due = self.search.last_edit
+            .is_some_and(|t| t.elapsed() >= SEARCH_DEBOUNCE);
+
+        if due {
+            self.run_search().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run_search(&mut self) -> LmsResult<()> {
+        self.search.last_edit = None;
+
+        let term = self.search.text.trim().to_string();
+        let title = format!("Search: {}", term);
+        self.browser = Browser::from(&title, BrowseQuery::Search(term.clone()));
+
+        if term.is_empty() {
+            if let Some(level) = self.browser.current_mut() {
+                level.loaded = true;
+            }
+            Ok(())
         } else {
-            None
+            self.load_browser_page().await
         }
     }
 
-    pub async fn get_current_playlist(&mut self) -> ReqResult<()> {
-        if let Some(playerid) = self.get_current_playerid() {
-            let command = json!([
-                playerid,
-                [ "status", 0, 9999, "tags:adl" ]
-            ]);
-            let res = self.query(command).await?;
-
-            let empty = Vec::new();
-            let playlist_loop = res.get_array("playlist_loop")
-                .unwrap_or(&empty);
-            let mut playlist: Vec<LmsSong> = Vec::new();
-            for track in playlist_loop.iter() {
-                playlist.push(
-                    from_str(&track.to_string()).unwrap()
-                );
+    pub async fn play_browser_item(
+        &mut self,
+        action: PlayAction
+    ) -> LmsResult<()> {
+        let target = self.browser
+            .selected_item()
+            .and_then(|item| item.play.clone());
+
+        if let Some(target) = target {
+            self.player_command(target.command(action)).await?;
+            if let PlayAction::Play = action {
+                self.follow_playing = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn seek_relative(&self, seconds: i64) -> LmsResult<()> {
+        if self.can_seek() {
+            self.player_command(LmsCommand::Time(Seek::By(seconds))).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn seek_to_fraction(&self, fraction: f64) -> LmsResult<()> {
+        if let Some(track) = self.current_track() {
+            if track.is_seekable() {
+                let position = track.duration * fraction.clamp(0.0, 1.0);
+                self.player_command(LmsCommand::Time(Seek::To(position))).await?;
             }
+        }
+
+        Ok(())
+    }
+
+    pub async fn seek_to_column(&self, column: u16) -> LmsResult<()> {
+        if let Some(area) = self.playbar_area {
+            let offset = column.saturating_sub(area.x) as f64;
+            self.seek_to_fraction(offset / area.width as f64).await?;
+        }
+
+        Ok(())
+    }
+
+    fn can_seek(&self) -> bool {
+        self.current_track()
+            .is_some_and(|track| track.is_seekable())
+    }
 
-            self.playlist = Some(LmsPlaylist::from(playlist));
+    pub fn current_track(&self) -> Option<&LmsSong> {
+        let status = self.status.as_ref()?;
+        let playlist = self.playlist.as_ref()?;
+
+        playlist.tracks.get(status.playlist_index as usize)
+    }
+
+    pub fn get_current_playerid(&self) -> Option<String> {
+        self.player.as_ref().map(|player| player.playerid.clone())
+    }
+
+    pub async fn get_current_playlist(&mut self) -> LmsResult<()> {
+        if let Some(playerid) = self.get_current_playerid() {
+            let command = LmsCommand::Status(
+                StatusQuery::range(0, 9999).tags("adlu")
+            );
+            let res: StatusResponse = self.client
+                .query(&playerid, &command)
+                .await?;
+
+            self.playlist = Some(LmsPlaylist::from(res.tracks));
         } else {
             self.playlist = None;
         }
@@ -155,73 +1364,27 @@ impl App {
         Ok(())
     }
 
-    pub async fn get_current_status(&mut self) -> ReqResult<()> {
+    pub async fn get_current_status(&mut self) -> LmsResult<()> {
         if let Some(playerid) = self.get_current_playerid() {
-            let command = json!([
-                playerid,
-                [ "status", 0, 9999 ]
-            ]);
-            let res = self.query(command).await?;
-
-            let player_name = res.get_str("player_name")
-                .expect("Could not extract value");
-            let total_tracks = res.get_u64("playlist_tracks")
-                .expect("Could not extract value");
-
-            let playlist_index: u64;
-            if total_tracks == 0 {
-                playlist_index = 0;
-            } else {
-                playlist_index = res.get_u64("playlist_cur_index")
-                    .unwrap_or_else(|_| {
-                        res.get_str("playlist_cur_index")
-                            .expect("Could not extract value")
-                            .parse::<u64>()
-                            .expect("Playlist index is not a u64")
-                    });
-            }
-            let playlist_repeat = RepeatMode::from(
-                res.get_u64("playlist repeat")
-                .expect("Could not extract value")
-            );
-            let playlist_shuffle = ShuffleMode::from(
-                res.get_u64("playlist shuffle")
-                .expect("Could not extract value")
-            );
-            let playlist_mode = PlaylistMode::from(
-                res.get_str("mode")
-                .expect("Could not extract value")
-                .as_str()
-            );
+            let command = LmsCommand::Status(StatusQuery::range(0, 9999));
+            let res: StatusResponse = self.client
+                .query(&playerid, &command)
+                .await?;
 
-            let command = json!([
-                playerid,
-                [ "time", "?" ]
-            ]);
-            let res = self.query(command).await?;
-
-            let elapsed_duration: f64;
-            if total_tracks == 0 {
-                elapsed_duration = 0.0;
-            } else if playlist_mode == PlaylistMode::STOP {
-                elapsed_duration = 0.0;
-            } else {
-                elapsed_duration = res.get_f64("_time")
-                    .unwrap_or_else(|_| {
-                        res.get_u64("_time")
-                            .expect("Could not extract value") as f64
-                    });
+            let mut status = LmsStatus::from_response(res)?;
+            self.check_playlist_timestamp(status.playlist_timestamp);
+
+            let res: TimeResponse = self.client
+                .query(&playerid, &LmsCommand::Time(Seek::Query))
+                .await?;
+
+            if status.total_tracks != 0
+                && status.playlist_mode != PlaylistMode::STOP
+            {
+                status.elapsed_duration = res.time;
             }
 
-            self.status = Some(LmsStatus {
-                player_name,
-                playlist_index,
-                playlist_repeat,
-                playlist_shuffle,
-                playlist_mode,
-                total_tracks,
-                elapsed_duration,
-            })
+            self.status = Some(status);
         } else {
             self.status = None;
         }
@@ -229,19 +1392,216 @@ impl App {
         Ok(())
     }
 
+    fn check_playlist_timestamp(&mut self, timestamp: Option<f64>) {
+        let expected = std::mem::replace(
+            &mut self.expecting_playlist_change,
+            false
+        );
+
+        // Pushes can be queued up from before our own edits were refreshed,
+        // so only a timestamp newer than the one we have means anything
+        let newer = match (timestamp, self.playlist_timestamp) {
+            (Some(timestamp), Some(known)) => timestamp > known,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if !newer {
+            return;
+        }
+
+        // Another client changed the playlist, so our history no longer
+        // matches the positions we recorded
+        if self.playlist_timestamp.is_some() && !expected {
+            self.history.clear();
+        }
+
+        self.playlist_timestamp = timestamp;
+    }
+
     fn update_state(&mut self) {
+        if !self.follow_playing && self.follow_timed_out() {
+            self.follow_playing = true;
+        }
+
         if let Some(status) = &self.status {
             if status.total_tracks == 0 {
                 self.playlist_state.select(None);
-            } else {
+            } else if self.follow_playing {
                 let index = status.playlist_index as usize;
                 self.playlist_state.select(Some(index));
+            } else {
+                // Keep the cursor where it is, unless the playlist shrank
+                let last = status.total_tracks as usize - 1;
+                let index = self.playlist_state.selected().unwrap_or(0);
+                self.playlist_state.select(Some(index.min(last)));
             }
         } else {
             self.playlist_state.select(None);
         }
     }
 
+    fn follow_timed_out(&self) -> bool {
+        let timeout = self.config.follow_timeout;
+        if timeout == 0 {
+            return false;
+        }
+
+        self.last_scroll
+            .is_some_and(|t| t.elapsed() >= Duration::from_secs(timeout))
+    }
+
+    pub fn follow_now_playing(&mut self) {
+        self.follow_playing = true;
+        self.update_state();
+    }
+
+    fn playlist_len(&self) -> usize {
+        self.playlist
+            .as_ref()
+            .map_or(0, |p| p.tracks.len())
+    }
+
+    fn move_playlist_cursor(&mut self, offset: isize) {
+        let len = self.playlist_len();
+        if len == 0 {
+            return;
+        }
+
+        let current = self.playlist_state.selected().unwrap_or(0) as isize;
+        let index = (current + offset).clamp(0, len as isize - 1);
+        self.set_playlist_cursor(index as usize);
+    }
+
+    fn set_playlist_cursor(&mut self, index: usize) {
+        self.playlist_state.select(Some(index));
+        self.follow_playing = false;
+        self.last_scroll = Some(Instant::now());
+    }
+
+    pub fn playlist_down(&mut self) {
+        self.move_playlist_cursor(1);
+    }
+
+    pub fn playlist_up(&mut self) {
+        self.move_playlist_cursor(-1);
+    }
+
+    pub fn playlist_page_down(&mut self) {
+        self.move_playlist_cursor(self.playlist_page_size as isize);
+    }
+
+    pub fn playlist_page_up(&mut self) {
+        self.move_playlist_cursor(-(self.playlist_page_size as isize));
+    }
+
+    pub fn jump_to_playlist_top(&mut self) {
+        if self.playlist_len() != 0 {
+            self.set_playlist_cursor(0);
+        }
+    }
+
+    pub fn jump_to_playlist_bottom(&mut self) {
+        let len = self.playlist_len();
+        if len != 0 {
+            self.set_playlist_cursor(len - 1);
+        }
+    }
+
+    fn focused_player(&self) -> Option<&LmsPlayer> {
+        self.player_list.state
+            .selected()
+            .and_then(|index| self.player_list.players.get(index))
+    }
+
+    pub async fn toggle_focused_player_power(&mut self) -> LmsResult<()> {
+        // Some players can't be switched off at all
+        let player = self.focused_player().filter(|player| player.canpoweroff);
+        if let Some(player) = player {
+            let command = LmsCommand::Power(!player.is_powered());
+            self.client.request(&player.playerid, &command).await?;
+            self.update_player_list().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn open_sync_popup(&mut self) -> LmsResult<()> {
+        let playerid = match self.focused_player() {
+            Some(player) => player.playerid.clone(),
+            None => return Ok(()),
+        };
+
+        let res: SyncGroupsResponse = self.client
+            .query("-", &LmsCommand::SyncGroups)
+            .await?;
+
+        // Existing groups come first, followed by every player that isn't
+        // synced to anything yet
+        let mut targets = Vec::new();
+        let mut grouped = Vec::new();
+        for group in res.groups.iter() {
+            let members: Vec<String> = group.members
+                .split(',')
+                .map(|s| s.to_string())
+                .collect();
+            let names = group.names.replace(',', ", ");
+
+            if !members.contains(&playerid) {
+                targets.push(PlayerTarget {
+                    name: format!("Group: {}", names),
+                    playerid: members[0].clone(),
+                });
+            }
+            grouped.extend(members);
+        }
+        for player in self.player_list.players.iter() {
+            if player.playerid != playerid
+                && !grouped.contains(&player.playerid)
+            {
+                targets.push(PlayerTarget {
+                    name: player.name.clone(),
+                    playerid: player.playerid.clone(),
+                });
+            }
+        }
+
+        self.popup = Some(Popup::SyncWith {
+            playerid,
+            targets,
+            selected: 0,
+        });
+
+        Ok(())
+    }
+
+    pub async fn sync_player(
+        &mut self,
+        playerid: &str,
+        target: &str
+    ) -> LmsResult<()> {
+        // The target keeps playing, and the player joining it follows along
+        let command = LmsCommand::Sync(playerid.to_string());
+        self.client.request(target, &command).await?;
+
+        Ok(())
+    }
+
+    pub async fn unsync_focused_player(&mut self) -> LmsResult<()> {
+        if let Some(player) = self.focused_player() {
+            self.client.request(&player.playerid, &LmsCommand::Unsync).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn player_name(&self, playerid: &str) -> String {
+        self.player_list.players
+            .iter()
+            .find(|player| player.playerid == playerid)
+            .map(|player| player.name.clone())
+            .unwrap_or_else(|| playerid.to_string())
+    }
+
     pub fn list_down(&mut self) {
         if !self.player_list.is_empty() {
             let i = match self.player_list.state.selected() {
@@ -274,15 +1634,73 @@ impl App {
         }
     }
 
+    pub fn dashboard_down(&mut self) {
+        self.move_dashboard_cursor(self.dashboard_columns as isize);
+    }
+
+    pub fn dashboard_up(&mut self) {
+        self.move_dashboard_cursor(-(self.dashboard_columns as isize));
+    }
+
+    pub fn move_dashboard_cursor(&mut self, offset: isize) {
+        // Moving off the edge of the grid stays put rather than wrapping,
+        // otherwise up and down would land in unrelated columns
+        if let Some(i) = self.player_list.state.selected() {
+            let target = i as isize + offset;
+            if target >= 0 && (target as usize) < self.player_list.players.len() {
+                self.player_list.state.select(Some(target as usize));
+            }
+        }
+    }
+
     pub fn jump_to_list_top(&mut self) {
-        if let Some(_) = self.player_list.state.selected() {
+        if self.player_list.state.selected().is_some() {
             self.player_list.state.select(Some(0));
         }
     }
 
     pub fn jump_to_list_bottom(&mut self) {
-        if let Some(_) = self.player_list.state.selected() {
+        if self.player_list.state.selected().is_some() {
             self.player_list.state.select(Some(self.player_list.players.len() - 1));
         }
     }
 }
+
+// Each failed attempt doubles the wait, so a server that is down for a
+// while isn't asked every second
+fn reconnect_delay(attempts: u32) -> Duration {
+    RECONNECT_MIN
+        .saturating_mul(1 << attempts.min(6))
+        .min(RECONNECT_MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_a_minute() {
+        let delays: Vec<u64> = (0..10)
+            .map(|attempts| reconnect_delay(attempts).as_secs())
+            .collect();
+
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60, 60, 60]);
+        assert_eq!(reconnect_delay(u32::MAX), RECONNECT_MAX);
+    }
+
+    #[test]
+    fn only_newer_playlist_timestamps_clear_history() {
+        let mut app = App::from(Config::default());
+        app.check_playlist_timestamp(Some(10.0));
+        app.history.record(PlaylistEdit::Move { from: 0, to: 1 });
+
+        // Pushes left over from before our own edit was refreshed
+        app.check_playlist_timestamp(Some(9.5));
+        app.check_playlist_timestamp(Some(10.0));
+        app.check_playlist_timestamp(None);
+        assert!(app.history.undo().is_some());
+
+        app.check_playlist_timestamp(Some(11.0));
+        assert!(app.history.redo().is_none());
+    }
+}
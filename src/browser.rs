@@ -0,0 +1,571 @@
+use ratatui::widgets::ListState;
+
+use crate::command::*;
+use crate::lms::*;
+
+pub const PAGE_SIZE: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayAction {
+    Play,
+    Append,
+    InsertNext,
+}
+
+#[derive(Clone, Debug)]
+pub enum PlayTarget {
+    Control(String),
+    Items { command: String, item_id: String },
+}
+
+impl PlayTarget {
+    pub fn command(&self, action: PlayAction) -> LmsCommand {
+        match self {
+            PlayTarget::Control(param) => LmsCommand::PlaylistControl {
+                action,
+                param: param.clone(),
+            },
+            PlayTarget::Items { command, item_id } => LmsCommand::PlayItem {
+                command: command.clone(),
+                action,
+                item_id: item_id.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BrowseQuery {
+    Menu(Vec<BrowseItem>),
+    Artists(Vec<String>),
+    Albums(Vec<String>),
+    Genres,
+    Years,
+    Titles(Vec<String>),
+    Search(String),
+    Playlists,
+    PlaylistTracks(String),
+    Radios,
+    Apps,
+    Items {
+        command: String,
+        item_id: Option<String>,
+        search: Option<String>,
+    },
+    MusicFolder(Option<String>),
+}
+
+impl BrowseQuery {
+    // Menus are built by lyra itself, so they never hit the server
+    pub fn static_items(&self) -> Option<Vec<BrowseItem>> {
+        match self {
+            BrowseQuery::Menu(items) => Some(items.clone()),
+            _ => None,
+        }
+    }
+
+    // Fill in the text the user typed for a search-type menu item
+    pub fn with_search(&self, term: &str) -> BrowseQuery {
+        match self {
+            BrowseQuery::Items { command, item_id, .. } => BrowseQuery::Items {
+                command: command.clone(),
+                item_id: item_id.clone(),
+                search: Some(term.to_string()),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    pub fn command(&self, start: usize) -> Option<LmsCommand> {
+        let (name, params) = match self {
+            BrowseQuery::Menu(_) => return None,
+            BrowseQuery::Artists(filters) => (vec!["artists"], filters.clone()),
+            BrowseQuery::Albums(filters) => {
+                (vec!["albums"], with_filter(filters, "tags:la"))
+            },
+            BrowseQuery::Genres => (vec!["genres"], Vec::new()),
+            BrowseQuery::Years => (vec!["years"], Vec::new()),
+            BrowseQuery::Titles(filters) => {
+                let mut params = with_filter(filters, "tags:adl");
+                if filters.iter().any(|f| f.starts_with("album_id:")) {
+                    params.push("sort:tracknum".to_string());
+                }
+                (vec!["titles"], params)
+            },
+            BrowseQuery::Search(term) => {
+                (vec!["search"], vec![format!("term:{}", term)])
+            },
+            BrowseQuery::Playlists => (vec!["playlists"], Vec::new()),
+            BrowseQuery::PlaylistTracks(id) => (
+                vec!["playlists", "tracks"],
+                vec![format!("playlist_id:{}", id), "tags:a".to_string()]
+            ),
+            BrowseQuery::Radios => (vec!["radios"], Vec::new()),
+            BrowseQuery::Apps => (vec!["apps"], Vec::new()),
+            BrowseQuery::Items { command, item_id, search } => {
+                let mut params = Vec::new();
+                if let Some(item_id) = item_id {
+                    params.push(format!("item_id:{}", item_id));
+                }
+                if let Some(search) = search {
+                    params.push(format!("search:{}", search));
+                }
+                (vec![command.as_str(), "items"], params)
+            },
+            BrowseQuery::MusicFolder(folder_id) => (
+                vec!["musicfolder"],
+                folder_id
+                    .iter()
+                    .map(|id| format!("folder_id:{}", id))
+                    .collect()
+            ),
+        };
+
+        Some(LmsCommand::Browse {
+            command: name.into_iter().map(|word| word.to_string()).collect(),
+            start,
+            params,
+        })
+    }
+
+    pub fn parse(&self, res: BrowseResponse) -> (Vec<BrowseItem>, usize) {
+        let total = res.count as usize;
+        let items = match self {
+            BrowseQuery::Menu(items) => return (items.clone(), items.len()),
+            BrowseQuery::Search(_) => {
+                let items = parse_search_results(res);
+                let total = items.len();
+                return (items, total);
+            },
+            BrowseQuery::Artists(filters) => res.artists
+                .into_iter()
+                .map(|item| artist_item(item, filters))
+                .collect(),
+            BrowseQuery::Albums(_) => res.albums
+                .into_iter()
+                .map(album_item)
+                .collect(),
+            BrowseQuery::Genres => res.genres
+                .into_iter()
+                .map(genre_item)
+                .collect(),
+            BrowseQuery::Years => res.years
+                .into_iter()
+                .map(year_item)
+                .collect(),
+            BrowseQuery::Titles(_) => res.titles
+                .into_iter()
+                .map(track_item)
+                .collect(),
+            BrowseQuery::Playlists => res.playlists
+                .into_iter()
+                .map(playlist_item)
+                .collect(),
+            BrowseQuery::PlaylistTracks(_) => res.playlist_tracks
+                .into_iter()
+                .map(track_item)
+                .collect(),
+            BrowseQuery::Radios => res.radios
+                .into_iter()
+                .map(app_item)
+                .collect(),
+            BrowseQuery::Apps => res.apps
+                .into_iter()
+                .map(app_item)
+                .collect(),
+            BrowseQuery::Items { command, .. } => res.items
+                .into_iter()
+                .map(|item| menu_item(item, command))
+                .collect(),
+            BrowseQuery::MusicFolder(_) => res.folder
+                .into_iter()
+                .map(folder_item)
+                .collect(),
+        };
+
+        (items, total)
+    }
+}
+
+fn artist_item(item: BrowseEntry, filters: &[String]) -> BrowseItem {
+    let param = format!("artist_id:{}", item.id);
+    BrowseItem::new(item.artist)
+        .browse(BrowseQuery::Albums(with_filter(filters, &param)))
+        .play(PlayTarget::Control(param))
+}
+
+fn album_item(item: BrowseEntry) -> BrowseItem {
+    let param = format!("album_id:{}", item.id);
+    BrowseItem::new(item.album)
+        .detail(item.artist)
+        .browse(BrowseQuery::Titles(vec![param.clone()]))
+        .play(PlayTarget::Control(param))
+}
+
+fn genre_item(item: BrowseEntry) -> BrowseItem {
+    let param = format!("genre_id:{}", item.id);
+    BrowseItem::new(item.genre)
+        .browse(BrowseQuery::Artists(vec![param.clone()]))
+        .play(PlayTarget::Control(param))
+}
+
+fn year_item(item: BrowseEntry) -> BrowseItem {
+    let param = format!("year:{}", item.year);
+    BrowseItem::new(item.year)
+        .browse(BrowseQuery::Albums(vec![param.clone()]))
+        .play(PlayTarget::Control(param))
+}
+
+fn track_item(item: BrowseEntry) -> BrowseItem {
+    let param = format!("track_id:{}", item.id);
+    BrowseItem::new(item.title)
+        .detail(item.artist)
+        .play(PlayTarget::Control(param))
+}
+
+fn playlist_item(item: BrowseEntry) -> BrowseItem {
+    let id = item.id;
+    BrowseItem::new(item.playlist)
+        .id(id.clone())
+        .browse(BrowseQuery::PlaylistTracks(id.clone()))
+        .play(PlayTarget::Control(format!("playlist_id:{}", id)))
+}
+
+fn app_item(item: BrowseEntry) -> BrowseItem {
+    BrowseItem::new(item.name)
+        .browse(BrowseQuery::Items {
+            command: item.cmd,
+            item_id: None,
+            search: None,
+        })
+}
+
+fn menu_item(item: BrowseEntry, command: &str) -> BrowseItem {
+    let id = item.id;
+    let mut menu_item = BrowseItem::new(item.name)
+        .id(id.clone());
+    let query = BrowseQuery::Items {
+        command: command.to_string(),
+        item_id: Some(id.clone()),
+        search: None,
+    };
+
+    if item.kind == "search" {
+        menu_item = menu_item.browse(query).needs_input();
+    } else if item.hasitems {
+        menu_item = menu_item.browse(query);
+    }
+    if item.isaudio {
+        menu_item = menu_item.play(PlayTarget::Items {
+            command: command.to_string(),
+            item_id: id,
+        });
+    }
+
+    menu_item
+}
+
+fn folder_item(item: BrowseEntry) -> BrowseItem {
+    let id = item.id;
+    let entry = BrowseItem::new(item.filename);
+
+    // Playing a folder queues everything beneath it
+    match item.kind.as_str() {
+        "folder" => entry
+            .browse(BrowseQuery::MusicFolder(Some(id.clone())))
+            .play(PlayTarget::Control(format!("folder_id:{}", id))),
+        "track" => entry
+            .play(PlayTarget::Control(format!("track_id:{}", id))),
+        "playlist" => entry
+            .play(PlayTarget::Control(format!("playlist_id:{}", id))),
+        _ => entry,
+    }
+}
+
+fn parse_search_results(res: BrowseResponse) -> Vec<BrowseItem> {
+    let mut items = Vec::new();
+
+    if !res.contributors.is_empty() {
+        items.push(BrowseItem::header("Artists".to_string()));
+    }
+    for result in res.contributors {
+        let param = format!("artist_id:{}", result.contributor_id);
+        items.push(BrowseItem::new(result.contributor)
+            .browse(BrowseQuery::Albums(vec![param.clone()]))
+            .play(PlayTarget::Control(param)));
+    }
+
+    if !res.albums.is_empty() {
+        items.push(BrowseItem::header("Albums".to_string()));
+    }
+    for result in res.albums {
+        let param = format!("album_id:{}", result.album_id);
+        items.push(BrowseItem::new(result.album)
+            .browse(BrowseQuery::Titles(vec![param.clone()]))
+            .play(PlayTarget::Control(param)));
+    }
+
+    if !res.tracks.is_empty() {
+        items.push(BrowseItem::header("Tracks".to_string()));
+    }
+    for result in res.tracks {
+        let param = format!("track_id:{}", result.track_id);
+        items.push(BrowseItem::new(result.track)
+            .play(PlayTarget::Control(param)));
+    }
+
+    items
+}
+
+pub fn favorites_menu() -> BrowseQuery {
+    BrowseQuery::Items {
+        command: "favorites".to_string(),
+        item_id: None,
+        search: None,
+    }
+}
+
+pub fn apps_menu() -> BrowseQuery {
+    BrowseQuery::Menu(vec![
+        BrowseItem::new("Radio".to_string())
+            .browse(BrowseQuery::Radios),
+        BrowseItem::new("Apps".to_string())
+            .browse(BrowseQuery::Apps),
+    ])
+}
+
+pub fn library_menu() -> BrowseQuery {
+    BrowseQuery::Menu(vec![
+        BrowseItem::new("Artists".to_string())
+            .browse(BrowseQuery::Artists(Vec::new())),
+        BrowseItem::new("Albums".to_string())
+            .browse(BrowseQuery::Albums(Vec::new())),
+        BrowseItem::new("Genres".to_string())
+            .browse(BrowseQuery::Genres),
+        BrowseItem::new("Years".to_string())
+            .browse(BrowseQuery::Years),
+        BrowseItem::new("Tracks".to_string())
+            .browse(BrowseQuery::Titles(Vec::new())),
+    ])
+}
+
+fn with_filter(filters: &[String], filter: &str) -> Vec<String> {
+    let mut filters = filters.to_vec();
+    filters.push(filter.to_string());
+
+    filters
+}
+
+#[derive(Clone, Debug)]
+pub struct BrowseItem {
+    pub id: Option<String>,
+    pub name: String,
+    pub detail: Option<String>,
+    pub browse: Option<BrowseQuery>,
+    pub play: Option<PlayTarget>,
+    pub header: bool,
+    pub input: bool,
+}
+
+impl BrowseItem {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: None,
+            name,
+            detail: None,
+            browse: None,
+            play: None,
+            header: false,
+            input: false,
+        }
+    }
+
+    // Section headers group items together, but can't be selected
+    pub fn header(name: String) -> Self {
+        Self {
+            header: true,
+            ..BrowseItem::new(name)
+        }
+    }
+
+    pub fn id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn detail(mut self, detail: String) -> Self {
+        if !detail.is_empty() {
+            self.detail = Some(detail);
+        }
+        self
+    }
+
+    pub fn browse(mut self, query: BrowseQuery) -> Self {
+        self.browse = Some(query);
+        self
+    }
+
+    // Opening the item asks the user for some text first, e.g. a search
+    pub fn needs_input(mut self) -> Self {
+        self.input = true;
+        self
+    }
+
+    pub fn play(mut self, target: PlayTarget) -> Self {
+        self.play = Some(target);
+        self
+    }
+}
+
+pub struct BrowseLevel {
+    pub title: String,
+    pub query: BrowseQuery,
+    pub items: Vec<BrowseItem>,
+    pub total: usize,
+    pub loaded: bool,
+    pub state: ListState,
+}
+
+impl BrowseLevel {
+    fn from(title: String, query: BrowseQuery) -> Self {
+        Self {
+            title,
+            query,
+            items: Vec::new(),
+            total: 0,
+            loaded: false,
+            state: ListState::default(),
+        }
+    }
+
+    pub fn append_page(&mut self, items: Vec<BrowseItem>, total: usize) {
+        // An empty page means the server has nothing more to give us,
+        // whatever the reported total says
+        let exhausted = items.is_empty();
+        self.items.extend(items);
+        self.total = if exhausted {
+            self.items.len()
+        } else {
+            total.max(self.items.len())
+        };
+        self.loaded = true;
+
+        match self.state.selected() {
+            _ if self.items.is_empty() => self.state.select(None),
+            Some(index) if index < self.items.len() => {},
+            Some(_) => self.jump_to_bottom(),
+            None => self.jump_to_top(),
+        }
+    }
+
+    // Forget everything we loaded, but keep the cursor roughly in place
+    pub fn reset(&mut self) {
+        self.items.clear();
+        self.total = 0;
+        self.loaded = false;
+    }
+
+    pub fn needs_more(&self) -> bool {
+        if !self.loaded {
+            return true;
+        }
+        if self.items.len() >= self.total {
+            return false;
+        }
+
+        // Fetch the next page a little before the cursor reaches the end
+        let index = self.state.selected().unwrap_or(0);
+        index + PAGE_SIZE / 4 >= self.items.len()
+    }
+
+    pub fn selected_item(&self) -> Option<&BrowseItem> {
+        self.items.get(self.state.selected()?)
+    }
+
+    pub fn move_cursor(&mut self, offset: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let index = (current + offset).clamp(0, self.items.len() as isize - 1);
+        let forward = offset >= 0;
+        self.select_nearest(index as usize, forward);
+    }
+
+    pub fn jump_to_top(&mut self) {
+        if !self.items.is_empty() {
+            self.select_nearest(0, true);
+        }
+    }
+
+    pub fn jump_to_bottom(&mut self) {
+        if !self.items.is_empty() {
+            self.select_nearest(self.items.len() - 1, false);
+        }
+    }
+
+    // Skip over section headers, looking the other way if we hit the end
+    fn select_nearest(&mut self, index: usize, forward: bool) {
+        let selectable = |i: &usize| !self.items[*i].header;
+        let ahead = if forward {
+            (index..self.items.len()).find(selectable)
+        } else {
+            (0..=index).rev().find(selectable)
+        };
+        let behind = if forward {
+            (0..index).rev().find(selectable)
+        } else {
+            (index + 1..self.items.len()).find(selectable)
+        };
+
+        if let Some(index) = ahead.or(behind) {
+            self.state.select(Some(index));
+        }
+    }
+}
+
+pub struct Browser {
+    pub levels: Vec<BrowseLevel>,
+}
+
+impl Browser {
+    pub fn from(title: &str, query: BrowseQuery) -> Self {
+        Self {
+            levels: vec![BrowseLevel::from(title.to_string(), query)],
+        }
+    }
+
+    pub fn current(&self) -> Option<&BrowseLevel> {
+        self.levels.last()
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut BrowseLevel> {
+        self.levels.last_mut()
+    }
+
+    pub fn selected_item(&self) -> Option<&BrowseItem> {
+        self.current()?.selected_item()
+    }
+
+    pub fn enter(&mut self, title: String, query: BrowseQuery) {
+        self.levels.push(BrowseLevel::from(title, query));
+    }
+
+    // Returns false when already at the root level
+    pub fn back(&mut self) -> bool {
+        if self.levels.len() > 1 {
+            self.levels.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<&str> {
+        self.levels
+            .iter()
+            .map(|l| l.title.as_str())
+            .collect()
+    }
+}
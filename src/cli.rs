@@ -0,0 +1,201 @@
+use std::{io, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc,
+    task::JoinHandle,
+    time::timeout,
+};
+
+// Only the notifications that change what we show are worth hearing about
+const SUBSCRIPTIONS: &str = "playlist,mixer,power,pause,play,stop,time,client,sync";
+
+#[derive(Debug, PartialEq)]
+pub enum CliEvent {
+    Notification { playerid: String, command: Vec<String> },
+    Disconnected,
+}
+
+impl CliEvent {
+    pub fn from(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace().map(url_decode);
+        let playerid = words.next()?;
+        let command: Vec<String> = words.collect();
+        if command.is_empty() {
+            return None;
+        }
+
+        Some(CliEvent::Notification { playerid, command })
+    }
+
+    pub fn concerns(&self, playerid: &str) -> bool {
+        match self {
+            CliEvent::Notification { playerid: id, command } => {
+                // Sync changes affect every player in the group, not just
+                // the one that was moved
+                id == playerid || command[0] == "sync"
+            },
+            CliEvent::Disconnected => true,
+        }
+    }
+}
+
+pub struct CliListener {
+    receiver: mpsc::UnboundedReceiver<CliEvent>,
+    task: JoinHandle<()>,
+}
+
+impl CliListener {
+    pub async fn connect(address: &str) -> io::Result<Self> {
+        let stream = timeout(
+            Duration::from_millis(1000),
+            TcpStream::connect(address)
+        ).await??;
+        let (reader, mut writer) = stream.into_split();
+
+        writer.write_all(
+            format!("subscribe {}\n", SUBSCRIPTIONS).as_bytes()
+        ).await?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            // The writer has to live as long as the reader, otherwise the
+            // server sees the connection as closed
+            let _writer = writer;
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(event) = CliEvent::from(&line) {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+
+            let _ = sender.send(CliEvent::Disconnected);
+        });
+
+        Ok(Self { receiver, task })
+    }
+
+    pub fn events(&mut self) -> Vec<CliEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+
+        events
+    }
+}
+
+impl Drop for CliListener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn url_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn decodes_escaped_words() {
+        assert_eq!(url_decode("00%3A04%3A20"), "00:04:20");
+        assert_eq!(url_decode("Caf%C3%A9%20Del%20Mar"), "Café Del Mar");
+        assert_eq!(url_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn parses_notifications() {
+        let event = CliEvent::from("aa%3Abb mixer volume 35").unwrap();
+        assert_eq!(
+            event,
+            CliEvent::Notification {
+                playerid: "aa:bb".to_string(),
+                command: vec![
+                    "mixer".to_string(),
+                    "volume".to_string(),
+                    "35".to_string(),
+                ],
+            }
+        );
+        assert!(event.concerns("aa:bb"));
+        assert!(!event.concerns("cc:dd"));
+        assert_eq!(CliEvent::from("aa%3Abb"), None);
+    }
+
+    #[tokio::test]
+    async fn streams_events_from_server() {
+        let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        let fake = tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+
+            let mut request = String::new();
+            BufReader::new(reader).read_line(&mut request).await.unwrap();
+            assert!(request.starts_with("subscribe "));
+            assert!(request.contains("playlist"));
+
+            writer.write_all(
+                b"aa%3Abb playlist newsong Song%20Title 3\n\
+                  cc%3Add power 0\n"
+            ).await.unwrap();
+        });
+
+        let mut listener = CliListener::connect(&address).await.unwrap();
+        fake.await.unwrap();
+
+        let mut events = Vec::new();
+        while !events.contains(&CliEvent::Disconnected) {
+            match listener.receiver.recv().await {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                CliEvent::Notification {
+                    playerid: "aa:bb".to_string(),
+                    command: vec![
+                        "playlist".to_string(),
+                        "newsong".to_string(),
+                        "Song Title".to_string(),
+                        "3".to_string(),
+                    ],
+                },
+                CliEvent::Notification {
+                    playerid: "cc:dd".to_string(),
+                    command: vec!["power".to_string(), "0".to_string()],
+                },
+                CliEvent::Disconnected,
+            ]
+        );
+    }
+}
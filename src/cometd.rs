@@ -0,0 +1,362 @@
+use serde_json::json;
+use std::time::Duration;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::command::*;
+use crate::lms::*;
+
+type JsonValue = serde_json::Value;
+
+// The server pushes a fresh status whenever something changes, and at
+// least this often (in seconds) otherwise
+const STATUS_INTERVAL: u64 = 10;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+// Long polls are held open by the server until it has something to say,
+// which can take a while when nothing is playing
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(90);
+
+pub enum CometdEvent {
+    Status(LmsStatus),
+    Playlist { playlist: LmsPlaylist, timestamp: Option<f64> },
+    Disconnected,
+}
+
+pub struct CometdSubscription {
+    pub playerid: String,
+    receiver: mpsc::UnboundedReceiver<CometdEvent>,
+    task: JoinHandle<()>,
+}
+
+impl CometdSubscription {
+    pub async fn subscribe(
+        client: LmsClient,
+        playerid: &str
+    ) -> LmsResult<Self> {
+        let replies = client.cometd(json!([{
+            "channel": "/meta/handshake",
+            "version": "1.0",
+            "supportedConnectionTypes": ["long-polling"],
+        }]), REQUEST_TIMEOUT).await?;
+        let client_id = replies
+            .iter()
+            .find_map(|reply| reply["clientId"].as_str())
+            .ok_or_else(|| LmsError::MissingField("clientId".to_string()))?
+            .to_string();
+
+        // Everything the server sends us lands somewhere under our own
+        // channel, so that is all we need to listen to
+        let channel = format!("/{}/slim/playerstatus/{}", client_id, playerid);
+        let status = LmsCommand::Status(
+            StatusQuery::current()
+                .tags("adlu")
+                .subscribe(STATUS_INTERVAL)
+        );
+        let replies = client.cometd(json!([
+            {
+                "channel": "/meta/subscribe",
+                "clientId": client_id,
+                "subscription": format!("/{}/**", client_id),
+            },
+            {
+                "channel": "/slim/subscribe",
+                "clientId": client_id,
+                "data": {
+                    "request": [playerid, status],
+                    "response": channel,
+                },
+            },
+        ]), REQUEST_TIMEOUT).await?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut stream = StatusStream {
+            client,
+            playerid: playerid.to_string(),
+            channel,
+            sender,
+            playlist_timestamp: None,
+        };
+        let task = tokio::spawn(async move {
+            let connect = json!([{
+                "channel": "/meta/connect",
+                "clientId": client_id,
+                "connectionType": "long-polling",
+            }]);
+
+            let mut replies = replies;
+            loop {
+                if stream.forward(&replies).await.is_err() {
+                    break;
+                }
+
+                replies = match stream.client.cometd(
+                    connect.clone(),
+                    CONNECT_TIMEOUT
+                ).await {
+                    Ok(replies) => replies,
+                    Err(_) => break,
+                };
+            }
+
+            let _ = stream.sender.send(CometdEvent::Disconnected);
+        });
+
+        Ok(Self {
+            playerid: playerid.to_string(),
+            receiver,
+            task,
+        })
+    }
+
+    pub fn events(&mut self) -> Vec<CometdEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+
+        events
+    }
+}
+
+impl Drop for CometdSubscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct StatusStream {
+    client: LmsClient,
+    playerid: String,
+    channel: String,
+    sender: mpsc::UnboundedSender<CometdEvent>,
+    playlist_timestamp: Option<f64>,
+}
+
+impl StatusStream {
+    // Errors mean the session is gone, either because the server forgot
+    // about us or because nobody is listening on the other end anymore
+    async fn forward(&mut self, replies: &[JsonValue]) -> Result<(), ()> {
+        for reply in replies {
+            let channel = reply["channel"].as_str().unwrap_or_default();
+            if channel.starts_with("/meta/")
+                && reply["successful"].as_bool() == Some(false)
+            {
+                return Err(());
+            }
+            if channel != self.channel {
+                continue;
+            }
+
+            let res = serde_json::from_value::<StatusResponse>(
+                reply["data"].clone()
+            );
+            let status = match res.map(LmsStatus::from_response) {
+                Ok(Ok(status)) => status,
+                _ => continue,
+            };
+
+            // Pushes only carry the current track, and the whole playlist
+            // is only worth fetching when it actually changed
+            if status.playlist_timestamp != self.playlist_timestamp {
+                self.playlist_timestamp = status.playlist_timestamp;
+                let playlist = self.fetch_playlist().await.map_err(|_| ())?;
+                self.send(CometdEvent::Playlist {
+                    playlist,
+                    timestamp: status.playlist_timestamp,
+                })?;
+            }
+            self.send(CometdEvent::Status(status))?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_playlist(&self) -> LmsResult<LmsPlaylist> {
+        let command = LmsCommand::Status(
+            StatusQuery::range(0, 9999).tags("adlu")
+        );
+        let res: StatusResponse = self.client
+            .query(&self.playerid, &command)
+            .await?;
+
+        Ok(LmsPlaylist::from(res.tracks))
+    }
+
+    fn send(&self, event: CometdEvent) -> Result<(), ()> {
+        self.sender.send(event).map_err(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    // Answers every POST with whatever the handler makes of its body, for as
+    // long as the test keeps running
+    async fn fake_server(
+        handler: fn(&JsonValue) -> JsonValue
+    ) -> String {
+        let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = server.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut reader = BufReader::new(reader);
+                    loop {
+                        let mut length = 0;
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            if reader.read_line(&mut line).await.unwrap() == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                            let header = line.to_lowercase();
+                            if let Some(value) =
+                                header.strip_prefix("content-length:")
+                            {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+
+                        let mut body = vec![0; length];
+                        reader.read_exact(&mut body).await.unwrap();
+                        let body: JsonValue =
+                            serde_json::from_slice(&body).unwrap();
+                        let reply = handler(&body).to_string();
+
+                        writer.write_all(format!(
+                            "HTTP/1.1 200 OK\r\n\
+                             Content-Type: application/json\r\n\
+                             Content-Length: {}\r\n\r\n{}",
+                            reply.len(),
+                            reply
+                        ).as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        address
+    }
+
+    fn lms(body: &JsonValue) -> JsonValue {
+        // The playlist is fetched over JSON-RPC, not pushed
+        if body["method"] == "slim.request" {
+            assert_eq!(
+                body["params"],
+                json!(["aa:bb", ["status", 0, 9999, "tags:adlu"]])
+            );
+
+            return json!({
+                "result": {
+                    "player_name": "Kitchen",
+                    "playlist_loop": [
+                        { "playlist index": 0, "id": 7, "title": "So What" },
+                        { "playlist index": 1, "id": 8, "title": "Freddie" },
+                    ],
+                },
+            });
+        }
+
+        let messages = body.as_array().unwrap();
+        let channel = "/abc123/slim/playerstatus/aa:bb";
+        match messages[0]["channel"].as_str().unwrap() {
+            "/meta/handshake" => json!([{
+                "channel": "/meta/handshake",
+                "successful": true,
+                "clientId": "abc123",
+            }]),
+            "/meta/subscribe" => {
+                assert_eq!(messages[0]["subscription"], "/abc123/**");
+                assert_eq!(messages[1]["channel"], "/slim/subscribe");
+                assert_eq!(messages[1]["data"]["response"], channel);
+                assert_eq!(
+                    messages[1]["data"]["request"],
+                    json!(["aa:bb", ["status", "-", 1, "tags:adlu", "subscribe:10"]])
+                );
+
+                json!([
+                    { "channel": "/meta/subscribe", "successful": true },
+                    { "channel": "/slim/subscribe", "successful": true },
+                    {
+                        "channel": channel,
+                        "data": {
+                            "player_name": "Kitchen",
+                            "mode": "play",
+                            "playlist_tracks": 2,
+                            "playlist_cur_index": "1",
+                            "playlist_timestamp": 1712345678.123,
+                            "playlist_loop": [
+                                { "playlist index": 1, "id": 8, "title": "Freddie" },
+                            ],
+                        },
+                    },
+                ])
+            },
+            // The server forgetting about us ends the stream
+            _ => json!([{ "channel": "/meta/connect", "successful": false }]),
+        }
+    }
+
+    fn lms_without_client_id(_: &JsonValue) -> JsonValue {
+        json!([{ "channel": "/meta/handshake", "successful": true }])
+    }
+
+    #[tokio::test]
+    async fn streams_status_from_server() {
+        let address = fake_server(lms).await;
+        let client = LmsClient::from(address);
+        let mut subscription = CometdSubscription::subscribe(client, "aa:bb")
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = subscription.receiver.recv().await {
+            let done = matches!(event, CometdEvent::Disconnected);
+            events.push(event);
+            if done {
+                break;
+            }
+        }
+
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            CometdEvent::Playlist { playlist, .. } => {
+                assert_eq!(playlist.tracks.len(), 2);
+                assert_eq!(playlist.tracks[1].title, "Freddie");
+            },
+            _ => panic!("expected the playlist first"),
+        }
+        match &events[1] {
+            CometdEvent::Status(status) => {
+                assert_eq!(status.player_name, "Kitchen");
+                assert_eq!(status.playlist_mode, PlaylistMode::PLAY);
+                assert_eq!(status.playlist_index, 1);
+            },
+            _ => panic!("expected the status after the playlist"),
+        }
+        assert!(matches!(events[2], CometdEvent::Disconnected));
+    }
+
+    #[tokio::test]
+    async fn requires_a_client_id() {
+        let address = fake_server(lms_without_client_id).await;
+        let client = LmsClient::from(address);
+
+        match CometdSubscription::subscribe(client, "aa:bb").await {
+            Err(LmsError::MissingField(field)) => assert_eq!(field, "clientId"),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("subscribed without a client id"),
+        }
+    }
+}
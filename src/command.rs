@@ -0,0 +1,430 @@
+use serde::{Serialize, Serializer};
+use serde_json::json;
+
+use crate::browser::*;
+use crate::lms::*;
+
+type JsonValue = serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LmsCommand {
+    ServerStatus,
+    SyncGroups,
+    Status(StatusQuery),
+    Time(Seek),
+    Play,
+    Pause,
+    Stop,
+    Power(bool),
+    Sleep(u64),
+    Volume(i64),
+    ToggleMute,
+    Sync(String),
+    Unsync,
+    Playlist(PlaylistCommand),
+    PlaylistControl { action: PlayAction, param: String },
+    PlayItem { command: String, action: PlayAction, item_id: String },
+    Browse { command: Vec<String>, start: usize, params: Vec<String> },
+    DeletePlaylist(String),
+    AddFavorite { url: String, title: String },
+    DeleteFavorite(String),
+    Alarms,
+    Alarm(AlarmCommand),
+    PlayerPref(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusQuery {
+    start: Option<usize>,
+    count: usize,
+    tags: Option<String>,
+    subscribe: Option<u64>,
+}
+
+impl StatusQuery {
+    // Starting from '-' means starting from the current track
+    pub fn current() -> Self {
+        Self {
+            start: None,
+            count: 1,
+            tags: None,
+            subscribe: None,
+        }
+    }
+
+    pub fn range(start: usize, count: usize) -> Self {
+        Self {
+            start: Some(start),
+            count,
+            tags: None,
+            subscribe: None,
+        }
+    }
+
+    pub fn tags(mut self, tags: &str) -> Self {
+        self.tags = Some(tags.to_string());
+        self
+    }
+
+    pub fn subscribe(mut self, interval: u64) -> Self {
+        self.subscribe = Some(interval);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Seek {
+    Query,
+    To(f64),
+    By(i64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaylistCommand {
+    Jump(usize),
+    Skip(i64),
+    Repeat(RepeatMode),
+    Shuffle(ShuffleMode),
+    Clear,
+    Delete(usize),
+    Move { from: usize, to: usize },
+    AddUrl(String),
+    Save(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlarmCommand {
+    Add(Vec<String>),
+    Update(Vec<String>),
+    Enable { id: String, enabled: bool },
+    Delete(String),
+    EnableAll,
+    DisableAll,
+}
+
+impl LmsCommand {
+    // Library tracks can be added by id, several at a time
+    pub fn add_tracks(track_ids: &[i64]) -> Self {
+        let track_ids = track_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        LmsCommand::PlaylistControl {
+            action: PlayAction::Append,
+            param: format!("track_id:{}", track_ids),
+        }
+    }
+
+    pub fn args(&self) -> Vec<JsonValue> {
+        match self {
+            LmsCommand::ServerStatus => vec![json!("serverstatus"), json!(0)],
+            LmsCommand::SyncGroups => vec![json!("syncgroups"), json!("?")],
+            LmsCommand::Status(query) => {
+                let mut args = vec![
+                    json!("status"),
+                    match query.start {
+                        Some(start) => json!(start),
+                        None => json!("-"),
+                    },
+                    json!(query.count),
+                ];
+                if let Some(tags) = &query.tags {
+                    args.push(json!(format!("tags:{}", tags)));
+                }
+                if let Some(interval) = query.subscribe {
+                    args.push(json!(format!("subscribe:{}", interval)));
+                }
+                args
+            },
+            LmsCommand::Time(seek) => vec![
+                json!("time"),
+                match seek {
+                    Seek::Query => json!("?"),
+                    Seek::To(position) => json!(position),
+                    Seek::By(offset) => json!(format!("{:+}", offset)),
+                },
+            ],
+            LmsCommand::Play => vec![json!("play")],
+            LmsCommand::Pause => vec![json!("pause")],
+            LmsCommand::Stop => vec![json!("stop")],
+            LmsCommand::Power(on) => vec![json!("power"), json!(*on as u8)],
+            LmsCommand::Sleep(seconds) => vec![json!("sleep"), json!(seconds)],
+            LmsCommand::Volume(delta) => vec![
+                json!("mixer"),
+                json!("volume"),
+                json!(format!("{:+}", delta)),
+            ],
+            LmsCommand::ToggleMute => vec![json!("mixer"), json!("muting")],
+            LmsCommand::Sync(playerid) => vec![json!("sync"), json!(playerid)],
+            LmsCommand::Unsync => vec![json!("sync"), json!("-")],
+            LmsCommand::Playlist(command) => command.args(),
+            LmsCommand::PlaylistControl { action, param } => {
+                let cmd = match action {
+                    PlayAction::Play => "cmd:load",
+                    PlayAction::Append => "cmd:add",
+                    PlayAction::InsertNext => "cmd:insert",
+                };
+
+                vec![json!("playlistcontrol"), json!(cmd), json!(param)]
+            },
+            LmsCommand::PlayItem { command, action, item_id } => {
+                let cmd = match action {
+                    PlayAction::Play => "play",
+                    PlayAction::Append => "add",
+                    PlayAction::InsertNext => "insert",
+                };
+
+                vec![
+                    json!(command),
+                    json!("playlist"),
+                    json!(cmd),
+                    json!(format!("item_id:{}", item_id)),
+                ]
+            },
+            LmsCommand::Browse { command, start, params } => {
+                let mut args: Vec<JsonValue> = command
+                    .iter()
+                    .map(|word| json!(word))
+                    .collect();
+                args.push(json!(start));
+                args.push(json!(PAGE_SIZE));
+                args.extend(params.iter().map(|param| json!(param)));
+                args
+            },
+            LmsCommand::DeletePlaylist(id) => vec![
+                json!("playlists"),
+                json!("delete"),
+                json!(format!("playlist_id:{}", id)),
+            ],
+            LmsCommand::AddFavorite { url, title } => vec![
+                json!("favorites"),
+                json!("add"),
+                json!(format!("url:{}", url)),
+                json!(format!("title:{}", title)),
+            ],
+            LmsCommand::DeleteFavorite(id) => vec![
+                json!("favorites"),
+                json!("delete"),
+                json!(format!("item_id:{}", id)),
+            ],
+            LmsCommand::Alarms => vec![
+                json!("alarms"),
+                json!(0),
+                json!(100),
+                json!("filter:all"),
+            ],
+            LmsCommand::Alarm(command) => command.args(),
+            LmsCommand::PlayerPref(name) => vec![
+                json!("playerpref"),
+                json!(name),
+                json!("?"),
+            ],
+        }
+    }
+}
+
+pub fn queue_commands(tracks: &[LmsSong]) -> Vec<LmsCommand> {
+    // Library tracks can be added in batches by id, but remote tracks have
+    // negative ids and need to be added by url, one at a time, in order.
+    // Tracks with neither can't be added back at all, so they're skipped.
+    let mut commands = Vec::new();
+    let mut track_ids = Vec::new();
+    for track in tracks {
+        if track.id > 0 {
+            track_ids.push(track.id);
+            continue;
+        }
+        if !track.can_be_added() {
+            continue;
+        }
+
+        if !track_ids.is_empty() {
+            commands.push(LmsCommand::add_tracks(&track_ids));
+            track_ids.clear();
+        }
+        commands.push(LmsCommand::Playlist(
+            PlaylistCommand::AddUrl(track.url.clone())
+        ));
+    }
+
+    if !track_ids.is_empty() {
+        commands.push(LmsCommand::add_tracks(&track_ids));
+    }
+
+    commands
+}
+
+impl Serialize for LmsCommand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.args().serialize(serializer)
+    }
+}
+
+impl PlaylistCommand {
+    fn args(&self) -> Vec<JsonValue> {
+        let mut args = vec![json!("playlist")];
+        args.extend(match self {
+            PlaylistCommand::Jump(index) => vec![json!("index"), json!(index)],
+            PlaylistCommand::Skip(offset) => vec![
+                json!("index"),
+                json!(format!("{:+}", offset)),
+            ],
+            PlaylistCommand::Repeat(mode) => vec![
+                json!("repeat"),
+                json!(mode.value()),
+            ],
+            PlaylistCommand::Shuffle(mode) => vec![
+                json!("shuffle"),
+                json!(mode.value()),
+            ],
+            PlaylistCommand::Clear => vec![json!("clear")],
+            PlaylistCommand::Delete(index) => vec![json!("delete"), json!(index)],
+            PlaylistCommand::Move { from, to } => vec![
+                json!("move"),
+                json!(from),
+                json!(to),
+            ],
+            PlaylistCommand::AddUrl(url) => vec![json!("add"), json!(url)],
+            PlaylistCommand::Save(name) => vec![json!("save"), json!(name)],
+        });
+
+        args
+    }
+}
+
+impl AlarmCommand {
+    fn args(&self) -> Vec<JsonValue> {
+        let (action, params) = match self {
+            AlarmCommand::Add(params) => ("add", params.clone()),
+            AlarmCommand::Update(params) => ("update", params.clone()),
+            AlarmCommand::Enable { id, enabled } => (
+                "update",
+                vec![
+                    format!("id:{}", id),
+                    format!("enabled:{}", *enabled as u8),
+                ]
+            ),
+            AlarmCommand::Delete(id) => ("delete", vec![format!("id:{}", id)]),
+            AlarmCommand::EnableAll => ("enableall", Vec::new()),
+            AlarmCommand::DisableAll => ("disableall", Vec::new()),
+        };
+
+        let mut args = vec![json!("alarm"), json!(action)];
+        args.extend(params.into_iter().map(JsonValue::from));
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(command: LmsCommand) -> String {
+        serde_json::to_string(&command).unwrap()
+    }
+
+    #[test]
+    fn serializes_status_queries() {
+        assert_eq!(
+            serialize(LmsCommand::Status(
+                StatusQuery::range(0, 9999).tags("adlu")
+            )),
+            r#"["status",0,9999,"tags:adlu"]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::Status(
+                StatusQuery::current().tags("adl").subscribe(10)
+            )),
+            r#"["status","-",1,"tags:adl","subscribe:10"]"#
+        );
+    }
+
+    #[test]
+    fn serializes_transport_commands() {
+        assert_eq!(serialize(LmsCommand::Time(Seek::Query)), r#"["time","?"]"#);
+        assert_eq!(serialize(LmsCommand::Time(Seek::By(-10))), r#"["time","-10"]"#);
+        assert_eq!(serialize(LmsCommand::Time(Seek::By(60))), r#"["time","+60"]"#);
+        assert_eq!(serialize(LmsCommand::Time(Seek::To(12.5))), r#"["time",12.5]"#);
+        assert_eq!(serialize(LmsCommand::Power(false)), r#"["power",0]"#);
+        assert_eq!(
+            serialize(LmsCommand::Volume(5)),
+            r#"["mixer","volume","+5"]"#
+        );
+        assert_eq!(serialize(LmsCommand::Unsync), r#"["sync","-"]"#);
+    }
+
+    #[test]
+    fn serializes_playlist_commands() {
+        assert_eq!(
+            serialize(LmsCommand::Playlist(PlaylistCommand::Skip(-1))),
+            r#"["playlist","index","-1"]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::Playlist(PlaylistCommand::Move { from: 3, to: 1 })),
+            r#"["playlist","move",3,1]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::Playlist(
+                PlaylistCommand::Repeat(RepeatMode::PLAYLIST)
+            )),
+            r#"["playlist","repeat",2]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::add_tracks(&[4, 8])),
+            r#"["playlistcontrol","cmd:add","track_id:4,8"]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::PlaylistControl {
+                action: PlayAction::InsertNext,
+                param: "album_id:7".to_string(),
+            }),
+            r#"["playlistcontrol","cmd:insert","album_id:7"]"#
+        );
+    }
+
+    #[test]
+    fn queues_library_and_remote_tracks() {
+        let song = LmsSong::with_source;
+        let tracks = vec![
+            song(1, "file:///a.flac"),
+            song(2, "file:///b.flac"),
+            song(-3, "http://example.com/stream"),
+            song(-4, ""),
+            song(5, "file:///c.flac"),
+        ];
+
+        assert_eq!(
+            queue_commands(&tracks),
+            vec![
+                LmsCommand::add_tracks(&[1, 2]),
+                LmsCommand::Playlist(PlaylistCommand::AddUrl(
+                    "http://example.com/stream".to_string()
+                )),
+                LmsCommand::add_tracks(&[5]),
+            ]
+        );
+        assert!(queue_commands(&[song(0, "")]).is_empty());
+    }
+
+    #[test]
+    fn serializes_library_queries() {
+        assert_eq!(
+            serialize(LmsCommand::Browse {
+                command: vec!["playlists".to_string(), "tracks".to_string()],
+                start: 200,
+                params: vec!["playlist_id:3".to_string()],
+            }),
+            r#"["playlists","tracks",200,100,"playlist_id:3"]"#
+        );
+        assert_eq!(
+            serialize(LmsCommand::Alarm(AlarmCommand::Enable {
+                id: "a1".to_string(),
+                enabled: true,
+            })),
+            r#"["alarm","update","id:a1","enabled:1"]"#
+        );
+    }
+}
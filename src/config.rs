@@ -1,10 +1,29 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Transport {
+    HTTP,
+    CLI,
+    COMETD,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub lms_ip: String,
     pub lms_port: String,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default = "Config::default_volume_step")]
+    pub volume_step: u64,
+    #[serde(default = "Config::default_follow_timeout")]
+    pub follow_timeout: u64,
+    #[serde(default = "Config::default_transport")]
+    pub transport: Transport,
+    #[serde(default = "Config::default_cli_port")]
+    pub cli_port: u16,
     #[serde(default = "Config::default_colors")]
     pub colors: HashMap<String, u8>,
     #[serde(default = "Config::default_colors")]
@@ -27,14 +46,37 @@ impl Config {
         colors.insert("TrackAlbum".to_string(), 1);
         colors.insert("TrackDuration".to_string(), 6);
         colors.insert("PlaybarGauge".to_string(), 2);
+        colors.insert("VolumeGauge".to_string(), 4);
+        colors.insert("MutedIndicator".to_string(), 1);
 
         colors
     }
 
+    fn default_volume_step() -> u64 {
+        5
+    }
+
+    fn default_follow_timeout() -> u64 {
+        10
+    }
+
+    fn default_transport() -> Transport {
+        Transport::HTTP
+    }
+
+    fn default_cli_port() -> u16 {
+        9090
+    }
+
     pub fn default() -> Self {
         Self {
             lms_ip: "127.0.0.1".to_string(),
             lms_port: "9000".to_string(),
+            read_only: false,
+            volume_step: Config::default_volume_step(),
+            follow_timeout: Config::default_follow_timeout(),
+            transport: Config::default_transport(),
+            cli_port: Config::default_cli_port(),
             colors: Config::default_colors(),
             default_colors: Config::default_colors(),
         }
@@ -50,8 +92,8 @@ impl Config {
 
     fn default_color(&self, name: &str) -> &u8 {
         self.default_colors.get(name)
-            .expect(
-                &format!("'{}' is not a valid config option", name)
-            )
+            .unwrap_or_else(|| {
+                panic!("'{}' is not a valid config option", name)
+            })
     }
 }
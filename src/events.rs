@@ -3,26 +3,80 @@ use crossterm::{
         self,
         Event,
         KeyCode,
-        KeyEvent
+        KeyEvent,
+        KeyModifiers,
+        MouseButton,
+        MouseEvent,
+        MouseEventKind,
     },
 };
 use std::error::Error;
 
+use crate::alarms::*;
 use crate::app::*;
+use crate::browser::*;
 
 type DynResult<T> = Result<T, Box<dyn Error>>;
 
 pub async fn handle_events(app: &mut App) -> DynResult<()> {
-    if let Event::Key(key) = event::read()? {
-        match app.state {
+    let event = event::read()?;
+
+    // Errors go away with the next key, which still does what it normally
+    // would, so an error that keeps coming back can't lock up the controls
+    if let Event::Key(_) = event {
+        app.error = None;
+    }
+
+    match event {
+        Event::Key(key) if app.disconnected.is_some() => {
+            handle_disconnected_events(key, app);
+        },
+        Event::Key(key) if app.popup.is_some() => {
+            handle_popup_events(key, app).await?;
+        },
+        Event::Key(key) if app.search.editing => {
+            handle_search_prompt_events(key, app).await?;
+        },
+        // Searching needs a player to play the results on
+        Event::Key(key)
+            if key.code == KeyCode::Char('/') && app.player.is_some() =>
+        {
+            app.open_search();
+        },
+        Event::Key(key) => match app.state {
             AppState::PlayerMenu => handle_player_menu_events(key, app).await?,
-            AppState::Playlist => handle_playlist_events(key, app),
-        }
+            AppState::Dashboard => handle_dashboard_events(key, app).await?,
+            AppState::Playlist => handle_playlist_events(key, app).await?,
+            AppState::Library => handle_browser_events(key, app).await?,
+            AppState::Search => handle_browser_events(key, app).await?,
+            AppState::SavedPlaylists => {
+                handle_saved_playlists_events(key, app).await?;
+            },
+            AppState::Favorites => handle_favorites_events(key, app).await?,
+            AppState::Apps => handle_browser_events(key, app).await?,
+            AppState::MusicFolder => handle_browser_events(key, app).await?,
+            AppState::Alarms => handle_alarms_events(key, app).await?,
+        },
+        Event::Mouse(mouse) if app.disconnected.is_none() => {
+            if let AppState::Playlist = app.state {
+                handle_playlist_mouse_events(mouse, app).await?;
+            }
+        },
+        _ => {}
     }
 
     Ok(())
 }
 
+fn handle_disconnected_events(key: KeyEvent, app: &mut App) {
+    match key.code {
+        KeyCode::Char('q') => app.quit = true,
+        KeyCode::Esc => app.quit = true,
+        KeyCode::Char('r') | KeyCode::Enter => app.retry_now(),
+        _ => {}
+    }
+}
+
 async fn handle_player_menu_events(
     key: KeyEvent,
     app: &mut App
@@ -30,17 +84,11 @@ async fn handle_player_menu_events(
     match key.code {
         KeyCode::Char('q') => app.quit = true,
         KeyCode::Esc => app.quit = true,
-        KeyCode::Char(' ') => {
-            if !app.player_list.is_empty() {
-                app.select_player().await?;
-                app.change_state(AppState::Playlist);
-            }
-        },
-        KeyCode::Enter => {
-            if !app.player_list.is_empty() {
-                app.select_player().await?;
-                app.change_state(AppState::Playlist);
-            }
+        KeyCode::Char(' ') | KeyCode::Enter
+            if !app.player_list.is_empty() =>
+        {
+            app.select_player().await?;
+            app.change_state(AppState::Playlist);
         },
         KeyCode::Char('j') => app.list_down(),
         KeyCode::Down => app.list_down(),
@@ -50,16 +98,492 @@ async fn handle_player_menu_events(
         KeyCode::Home => app.jump_to_list_top(),
         KeyCode::Char('G') => app.jump_to_list_bottom(),
         KeyCode::End => app.jump_to_list_bottom(),
+        KeyCode::Tab => app.open_dashboard().await?,
+        KeyCode::Char('O') if !app.config.read_only => {
+            app.toggle_focused_player_power().await?;
+        },
+        KeyCode::Char('S') if !app.config.read_only => {
+            app.open_sync_popup().await?;
+        },
+        KeyCode::Char('U') if !app.config.read_only => {
+            app.unsync_focused_player().await?;
+        },
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_dashboard_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match key.code {
+        KeyCode::Char('q') => app.quit = true,
+        KeyCode::Esc | KeyCode::Tab => app.change_state(AppState::PlayerMenu),
+        KeyCode::Char(' ') | KeyCode::Enter
+            if !app.player_list.is_empty() =>
+        {
+            app.select_player().await?;
+            app.change_state(AppState::Playlist);
+        },
+        KeyCode::Char('j') => app.dashboard_down(),
+        KeyCode::Down => app.dashboard_down(),
+        KeyCode::Char('k') => app.dashboard_up(),
+        KeyCode::Up => app.dashboard_up(),
+        KeyCode::Char('l') => app.move_dashboard_cursor(1),
+        KeyCode::Right => app.move_dashboard_cursor(1),
+        KeyCode::Char('h') => app.move_dashboard_cursor(-1),
+        KeyCode::Left => app.move_dashboard_cursor(-1),
+        KeyCode::Char('g') => app.jump_to_list_top(),
+        KeyCode::Home => app.jump_to_list_top(),
+        KeyCode::Char('G') => app.jump_to_list_bottom(),
+        KeyCode::End => app.jump_to_list_bottom(),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_playlist_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    if let Some(count) = app.take_count() {
+        if handle_count_events(count, key, app).await? {
+            return Ok(());
+        }
+    }
+
+    match key.code {
+        KeyCode::Char('q') => app.quit = true,
+        KeyCode::Char(c @ '1'..='9') => {
+            app.push_count_digit(c.to_digit(10).unwrap_or(0) as usize);
+        },
+        KeyCode::Char('p') => app.change_state(AppState::PlayerMenu),
+        KeyCode::Char('j') => app.playlist_down(),
+        KeyCode::Down => app.playlist_down(),
+        KeyCode::Char('k') => app.playlist_up(),
+        KeyCode::Up => app.playlist_up(),
+        KeyCode::PageDown => app.playlist_page_down(),
+        KeyCode::PageUp => app.playlist_page_up(),
+        KeyCode::Char('g') => app.jump_to_playlist_top(),
+        KeyCode::Home => app.jump_to_playlist_top(),
+        KeyCode::Char('G') => app.jump_to_playlist_bottom(),
+        KeyCode::End => app.jump_to_playlist_bottom(),
+        KeyCode::Char('f') => app.follow_now_playing(),
+        KeyCode::Char('b') => app.open_library().await?,
+        KeyCode::Char('o') => app.open_saved_playlists().await?,
+        KeyCode::Char('F') => app.open_favorites().await?,
+        KeyCode::Char('I') => app.open_apps().await?,
+        KeyCode::Char('M') => app.open_music_folder().await?,
+        KeyCode::Char('A') => app.open_alarms().await?,
+        _ if !app.config.read_only => handle_playback_events(key, app).await?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Digits typed before a key are a count for it, e.g. 37G plays track 37.
+// Any other key (or a pause, see run_pending_count) lets a lone digit seek
+// like it always did, and the key then does what it normally does.
+async fn handle_count_events(
+    count: usize,
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<bool> {
+    match key.code {
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            app.count = Some(count);
+            app.push_count_digit(c.to_digit(10).unwrap_or(0) as usize);
+        },
+        KeyCode::Char('G') | KeyCode::Enter => app.go_to_track(count).await?,
+        KeyCode::Char('%') if !app.config.read_only => {
+            app.seek_to_fraction(count as f64 / 100.0).await?;
+        },
+        KeyCode::Esc => {},
+        _ => {
+            app.seek_to_tenth(count).await?;
+            return Ok(false);
+        },
+    }
+
+    Ok(true)
+}
+
+async fn handle_playback_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match key.code {
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.redo_edit().await?;
+        },
+        KeyCode::Char(' ') => app.toggle_pause().await?,
+        KeyCode::Char('s') => app.stop().await?,
+        KeyCode::Char('O') => app.toggle_power().await?,
+        KeyCode::Char('T') => app.open_transfer_popup().await?,
+        KeyCode::Char('t') => app.popup = Some(Popup::SleepTimer(0)),
+        KeyCode::Char('>') => app.next_track().await?,
+        KeyCode::Char('<') => app.previous_track().await?,
+        KeyCode::Char('0') => app.restart_track().await?,
+        KeyCode::Char('h') => app.seek_relative(-10).await?,
+        KeyCode::Left => app.seek_relative(-10).await?,
+        KeyCode::Char('l') => app.seek_relative(10).await?,
+        KeyCode::Right => app.seek_relative(10).await?,
+        KeyCode::Char('H') => app.seek_relative(-60).await?,
+        KeyCode::Char('L') => app.seek_relative(60).await?,
+        KeyCode::Char('+') => app.change_volume(true).await?,
+        KeyCode::Char('=') => app.change_volume(true).await?,
+        KeyCode::Char('-') => app.change_volume(false).await?,
+        KeyCode::Char('m') => app.toggle_mute().await?,
+        KeyCode::Char('r') => app.cycle_repeat().await?,
+        KeyCode::Char('z') => app.cycle_shuffle().await?,
+        KeyCode::Enter => app.play_selected_track().await?,
+        KeyCode::Char(':') => {
+            app.popup = Some(Popup::JumpToTrack(String::new()));
+        },
+        KeyCode::Char('d') => app.delete_selected_track().await?,
+        KeyCode::Char('J') => app.move_selected_track(true).await?,
+        KeyCode::Char('K') => app.move_selected_track(false).await?,
+        KeyCode::Char('C') => app.popup = Some(Popup::ConfirmClear),
+        KeyCode::Char('u') => app.undo_edit().await?,
+        KeyCode::Char('w') => {
+            app.popup = Some(Popup::SavePlaylist(String::new()));
+        },
+        KeyCode::Char('*') => app.add_current_track_to_favorites().await?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_browser_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match key.code {
+        KeyCode::Char('q') => app.quit = true,
+        KeyCode::Char('p') => app.change_state(AppState::PlayerMenu),
+        KeyCode::Char('j') => app.move_browser_cursor(1).await?,
+        KeyCode::Down => app.move_browser_cursor(1).await?,
+        KeyCode::Char('k') => app.move_browser_cursor(-1).await?,
+        KeyCode::Up => app.move_browser_cursor(-1).await?,
+        KeyCode::PageDown => app.browser_page_down().await?,
+        KeyCode::PageUp => app.browser_page_up().await?,
+        KeyCode::Char('g') => app.jump_to_browser_top(),
+        KeyCode::Home => app.jump_to_browser_top(),
+        KeyCode::Char('G') => app.jump_to_browser_bottom().await?,
+        KeyCode::End => app.jump_to_browser_bottom().await?,
+        KeyCode::Enter => app.browser_enter().await?,
+        KeyCode::Char('l') => app.browser_enter().await?,
+        KeyCode::Right => app.browser_enter().await?,
+        KeyCode::Esc => app.browser_back().await?,
+        KeyCode::Backspace => app.browser_back().await?,
+        KeyCode::Char('h') => app.browser_back().await?,
+        KeyCode::Left => app.browser_back().await?,
+        _ if !app.config.read_only => match key.code {
+            KeyCode::Char('P') => {
+                app.play_browser_item(PlayAction::Play).await?;
+            },
+            KeyCode::Char('a') => {
+                app.play_browser_item(PlayAction::Append).await?;
+            },
+            KeyCode::Char('n') => {
+                app.play_browser_item(PlayAction::InsertNext).await?;
+            },
+            _ => {}
+        },
         _ => {}
     }
 
     Ok(())
 }
 
-fn handle_playlist_events(key: KeyEvent, app: &mut App) {
+async fn handle_saved_playlists_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    // Deleting only makes sense on the list of playlists itself
+    let at_root = app.browser.levels.len() == 1;
+
+    match key.code {
+        KeyCode::Char('w') if !app.config.read_only => {
+            app.popup = Some(Popup::SavePlaylist(String::new()));
+        },
+        KeyCode::Char('d') if !app.config.read_only && at_root => {
+            if let Some(item) = app.browser.selected_item() {
+                if let Some(id) = &item.id {
+                    app.popup = Some(Popup::ConfirmDeletePlaylist {
+                        id: id.clone(),
+                        name: item.name.clone(),
+                    });
+                }
+            }
+        },
+        _ => handle_browser_events(key, app).await?,
+    }
+
+    Ok(())
+}
+
+async fn handle_favorites_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match key.code {
+        KeyCode::Char('d') if !app.config.read_only => {
+            if let Some(item) = app.browser.selected_item() {
+                if let Some(id) = &item.id {
+                    app.popup = Some(Popup::ConfirmDeleteFavorite {
+                        id: id.clone(),
+                        name: item.name.clone(),
+                    });
+                }
+            }
+        },
+        _ => handle_browser_events(key, app).await?,
+    }
+
+    Ok(())
+}
+
+async fn handle_alarms_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
     match key.code {
         KeyCode::Char('q') => app.quit = true,
         KeyCode::Char('p') => app.change_state(AppState::PlayerMenu),
+        KeyCode::Esc | KeyCode::Backspace => {
+            app.change_state(AppState::Playlist);
+        },
+        KeyCode::Char('j') | KeyCode::Down => app.move_alarms_cursor(1),
+        KeyCode::Char('k') | KeyCode::Up => app.move_alarms_cursor(-1),
+        KeyCode::Char('g') | KeyCode::Home => app.jump_to_alarms_top(),
+        KeyCode::Char('G') | KeyCode::End => app.jump_to_alarms_bottom(),
+        _ if !app.config.read_only => match key.code {
+            KeyCode::Char('n') => {
+                app.popup = Some(Popup::AlarmForm(AlarmForm::default()));
+            },
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(alarm) = app.selected_alarm() {
+                    app.popup = Some(Popup::AlarmForm(AlarmForm::from(alarm)));
+                }
+            },
+            KeyCode::Char('d') => {
+                if let Some(alarm) = app.selected_alarm() {
+                    app.popup = Some(Popup::ConfirmDeleteAlarm {
+                        id: alarm.id.clone(),
+                        time: format_alarm_time(alarm.time),
+                    });
+                }
+            },
+            KeyCode::Char(' ') => app.toggle_selected_alarm().await?,
+            KeyCode::Char('E') => app.toggle_all_alarms().await?,
+            _ => {}
+        },
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_search_prompt_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match key.code {
+        KeyCode::Esc => app.close_search().await?,
+        KeyCode::Enter => app.submit_search().await?,
+        KeyCode::Down | KeyCode::Tab => app.search.editing = false,
+        KeyCode::Backspace => app.edit_search(None),
+        KeyCode::Char(c) => app.edit_search(Some(c)),
         _ => {}
     }
+
+    Ok(())
+}
+
+async fn handle_popup_events(
+    key: KeyEvent,
+    app: &mut App
+) -> DynResult<()> {
+    match app.popup.take() {
+        Some(Popup::JumpToTrack(mut number)) => match key.code {
+            KeyCode::Esc => {},
+            KeyCode::Enter => {
+                if let Ok(track) = number.parse::<usize>() {
+                    if track > 0 {
+                        app.play_track(track - 1).await?;
+                    }
+                }
+            },
+            KeyCode::Backspace => {
+                number.pop();
+                app.popup = Some(Popup::JumpToTrack(number));
+            },
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                number.push(c);
+                app.popup = Some(Popup::JumpToTrack(number));
+            },
+            _ => app.popup = Some(Popup::JumpToTrack(number)),
+        },
+        Some(Popup::ConfirmClear) => match key.code {
+            KeyCode::Char('y') => app.clear_playlist().await?,
+            KeyCode::Enter => app.clear_playlist().await?,
+            KeyCode::Char('n') | KeyCode::Esc => {},
+            _ => app.popup = Some(Popup::ConfirmClear),
+        },
+        Some(Popup::SavePlaylist(mut name)) => match key.code {
+            KeyCode::Esc => {},
+            KeyCode::Enter => app.save_playlist(&name).await?,
+            KeyCode::Backspace => {
+                name.pop();
+                app.popup = Some(Popup::SavePlaylist(name));
+            },
+            KeyCode::Char(c) => {
+                name.push(c);
+                app.popup = Some(Popup::SavePlaylist(name));
+            },
+            _ => app.popup = Some(Popup::SavePlaylist(name)),
+        },
+        Some(Popup::ConfirmDeletePlaylist { id, name }) => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                app.delete_saved_playlist(&id).await?;
+            },
+            KeyCode::Char('n') | KeyCode::Esc => {},
+            _ => app.popup = Some(Popup::ConfirmDeletePlaylist { id, name }),
+        },
+        Some(Popup::ConfirmDeleteFavorite { id, name }) => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                app.delete_favorite(&id).await?;
+            },
+            KeyCode::Char('n') | KeyCode::Esc => {},
+            _ => app.popup = Some(Popup::ConfirmDeleteFavorite { id, name }),
+        },
+        Some(Popup::BrowseInput { title, query, mut text }) => match key.code {
+            KeyCode::Esc => {},
+            KeyCode::Enter => app.browse_with_input(&title, &query, &text).await?,
+            KeyCode::Backspace => {
+                text.pop();
+                app.popup = Some(Popup::BrowseInput { title, query, text });
+            },
+            KeyCode::Char(c) => {
+                text.push(c);
+                app.popup = Some(Popup::BrowseInput { title, query, text });
+            },
+            _ => app.popup = Some(Popup::BrowseInput { title, query, text }),
+        },
+        Some(Popup::SyncWith { playerid, targets, mut selected }) => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Enter => {
+                    if let Some(target) = targets.get(selected) {
+                        app.sync_player(&playerid, &target.playerid).await?;
+                    }
+                    return Ok(());
+                },
+                KeyCode::Char('j') | KeyCode::Down
+                    if selected + 1 < targets.len() =>
+                {
+                    selected += 1;
+                },
+                KeyCode::Char('k') | KeyCode::Up => {
+                    selected = selected.saturating_sub(1);
+                },
+                _ => {},
+            }
+            app.popup = Some(Popup::SyncWith { playerid, targets, selected });
+        },
+        Some(Popup::TransferTo { targets, mut selected }) => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Enter | KeyCode::Char('c') => {
+                    let stop_source = key.code == KeyCode::Enter;
+                    if let Some(target) = targets.get(selected) {
+                        app.transfer_queue(&target.playerid, stop_source).await?;
+                    }
+                    return Ok(());
+                },
+                KeyCode::Char('j') | KeyCode::Down
+                    if selected + 1 < targets.len() =>
+                {
+                    selected += 1;
+                },
+                KeyCode::Char('k') | KeyCode::Up => {
+                    selected = selected.saturating_sub(1);
+                },
+                _ => {},
+            }
+            app.popup = Some(Popup::TransferTo { targets, selected });
+        },
+        Some(Popup::SleepTimer(mut selected)) => {
+            let options = SleepTimer::options();
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Enter => {
+                    app.set_sleep_timer(options[selected]).await?;
+                    return Ok(());
+                },
+                KeyCode::Char('j') | KeyCode::Down
+                    if selected + 1 < options.len() =>
+                {
+                    selected += 1;
+                },
+                KeyCode::Char('k') | KeyCode::Up => {
+                    selected = selected.saturating_sub(1);
+                },
+                _ => {},
+            }
+            app.popup = Some(Popup::SleepTimer(selected));
+        },
+        Some(Popup::AlarmForm(mut form)) => {
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                // Forms that don't make sense stay open so they can be fixed
+                KeyCode::Enter if form.params().is_some() => {
+                    app.save_alarm(&form).await?;
+                    return Ok(());
+                },
+                KeyCode::Tab | KeyCode::Down => form.next_field(),
+                KeyCode::BackTab | KeyCode::Up => form.previous_field(),
+                KeyCode::Backspace => form.pop(),
+                KeyCode::Char(c) => form.push(c),
+                _ => {},
+            }
+            app.popup = Some(Popup::AlarmForm(form));
+        },
+        Some(Popup::ConfirmDeleteAlarm { id, time }) => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.delete_alarm(&id).await?,
+            KeyCode::Char('n') | KeyCode::Esc => {},
+            _ => app.popup = Some(Popup::ConfirmDeleteAlarm { id, time }),
+        },
+        None => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_playlist_mouse_events(
+    mouse: MouseEvent,
+    app: &mut App
+) -> DynResult<()> {
+    if app.config.read_only {
+        return Ok(());
+    }
+
+    if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+        if let Some(area) = app.playbar_area {
+            if mouse.row == area.y
+                && mouse.column >= area.x
+                && mouse.column < area.x + area.width
+            {
+                app.seek_to_column(mouse.column).await?;
+            }
+        }
+    }
+
+    Ok(())
 }
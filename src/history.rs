@@ -0,0 +1,180 @@
+use crate::command::*;
+use crate::lms::*;
+
+#[derive(Clone, Debug)]
+pub enum PlaylistEdit {
+    Delete { track: LmsSong, index: usize },
+    Move { from: usize, to: usize },
+    Clear { tracks: Vec<LmsSong> },
+}
+
+impl PlaylistEdit {
+    pub fn commands(&self) -> Vec<LmsCommand> {
+        let command = match self {
+            PlaylistEdit::Delete { index, .. } => {
+                PlaylistCommand::Delete(*index)
+            },
+            PlaylistEdit::Move { from, to } => {
+                PlaylistCommand::Move { from: *from, to: *to }
+            },
+            PlaylistEdit::Clear { .. } => PlaylistCommand::Clear,
+        };
+
+        vec![LmsCommand::Playlist(command)]
+    }
+
+    // The inverse needs the current playlist length, since re-added tracks
+    // always land at the end of the playlist before being moved into place
+    pub fn inverse_commands(&self, playlist_len: usize) -> Vec<LmsCommand> {
+        match self {
+            PlaylistEdit::Delete { track, index } => {
+                let mut commands = queue_commands(std::slice::from_ref(track));
+                if !commands.is_empty() {
+                    commands.push(LmsCommand::Playlist(PlaylistCommand::Move {
+                        from: playlist_len,
+                        to: *index,
+                    }));
+                }
+
+                commands
+            },
+            PlaylistEdit::Move { from, to } => vec![
+                LmsCommand::Playlist(PlaylistCommand::Move {
+                    from: *to,
+                    to: *from,
+                }),
+            ],
+            PlaylistEdit::Clear { tracks } => queue_commands(tracks),
+        }
+    }
+}
+
+pub struct PlaylistHistory {
+    undo: Vec<PlaylistEdit>,
+    redo: Vec<PlaylistEdit>,
+}
+
+impl PlaylistHistory {
+    pub fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, edit: PlaylistEdit) {
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<PlaylistEdit> {
+        let edit = self.undo.pop()?;
+        self.redo.push(edit.clone());
+
+        Some(edit)
+    }
+
+    pub fn redo(&mut self) -> Option<PlaylistEdit> {
+        let edit = self.redo.pop()?;
+        self.undo.push(edit.clone());
+
+        Some(edit)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undoes_a_delete() {
+        let edit = PlaylistEdit::Delete {
+            track: LmsSong::with_source(42, ""),
+            index: 3,
+        };
+
+        assert_eq!(
+            edit.commands(),
+            vec![LmsCommand::Playlist(PlaylistCommand::Delete(3))]
+        );
+        assert_eq!(
+            edit.inverse_commands(9),
+            vec![
+                LmsCommand::add_tracks(&[42]),
+                LmsCommand::Playlist(PlaylistCommand::Move { from: 9, to: 3 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn undoes_a_move() {
+        let edit = PlaylistEdit::Move { from: 1, to: 2 };
+
+        assert_eq!(
+            edit.inverse_commands(5),
+            vec![LmsCommand::Playlist(PlaylistCommand::Move { from: 2, to: 1 })]
+        );
+    }
+
+    #[test]
+    fn undoes_a_clear() {
+        let edit = PlaylistEdit::Clear {
+            tracks: vec![
+                LmsSong::with_source(1, ""),
+                LmsSong::with_source(2, ""),
+                LmsSong::with_source(3, ""),
+            ],
+        };
+
+        assert_eq!(
+            edit.commands(),
+            vec![LmsCommand::Playlist(PlaylistCommand::Clear)]
+        );
+        assert_eq!(
+            edit.inverse_commands(0),
+            vec![LmsCommand::add_tracks(&[1, 2, 3])]
+        );
+        assert!(PlaylistEdit::Clear { tracks: Vec::new() }
+            .inverse_commands(0)
+            .is_empty());
+    }
+
+    #[test]
+    fn restores_remote_tracks_by_url() {
+        let radio = "http://example.com/stream.mp3";
+        let edit = PlaylistEdit::Delete {
+            track: LmsSong::with_source(-7, radio),
+            index: 0,
+        };
+
+        assert_eq!(
+            edit.inverse_commands(4),
+            vec![
+                LmsCommand::Playlist(PlaylistCommand::AddUrl(radio.to_string())),
+                LmsCommand::Playlist(PlaylistCommand::Move { from: 4, to: 0 }),
+            ]
+        );
+
+        let edit = PlaylistEdit::Clear {
+            tracks: vec![
+                LmsSong::with_source(1, ""),
+                LmsSong::with_source(0, radio),
+                LmsSong::with_source(2, ""),
+            ],
+        };
+
+        assert_eq!(
+            edit.inverse_commands(0),
+            vec![
+                LmsCommand::add_tracks(&[1]),
+                LmsCommand::Playlist(PlaylistCommand::AddUrl(radio.to_string())),
+                LmsCommand::add_tracks(&[2]),
+            ]
+        );
+    }
+}
@@ -1,14 +1,69 @@
 use core::time::Duration;
 use std::fmt;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 use serde_json::json;
-use std::{
-    collections::HashMap,
-    net,
-};
+use std::net;
+
+use crate::command::*;
 
 type JsonValue = serde_json::Value;
 
+#[derive(Debug)]
+pub enum LmsError {
+    Transport(reqwest::Error),
+    HttpStatus(reqwest::StatusCode),
+    Json(serde_json::Error),
+    MissingField(String),
+    UnknownValue { field: String, value: String },
+}
+
+pub type LmsResult<T> = Result<T, LmsError>;
+
+impl fmt::Display for LmsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LmsError::Transport(err) => {
+                write!(f, "Could not reach the server: {}", err)
+            },
+            LmsError::HttpStatus(status) => {
+                write!(f, "The server answered with HTTP {}", status)
+            },
+            LmsError::Json(err) => {
+                write!(f, "The server sent malformed data: {}", err)
+            },
+            LmsError::MissingField(field) => {
+                write!(f, "The server did not send '{}'", field)
+            },
+            LmsError::UnknownValue { field, value } => {
+                write!(f, "The server sent an unknown '{}': '{}'", field, value)
+            },
+        }
+    }
+}
+
+impl std::error::Error for LmsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LmsError::Transport(err) => Some(err),
+            LmsError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LmsError {
+    fn from(err: reqwest::Error) -> Self {
+        LmsError::Transport(err)
+    }
+}
+
+impl From<serde_json::Error> for LmsError {
+    fn from(err: serde_json::Error) -> Self {
+        LmsError::Json(err)
+    }
+}
+
+#[derive(Clone)]
 pub struct LmsClient {
     client: reqwest::Client,
     socket: net::SocketAddr,
@@ -26,88 +81,275 @@ impl LmsClient {
         }
     }
 
-    pub async fn query(
+    pub async fn request(
         &self,
-        command: JsonValue
-    ) -> Result<reqwest::Response, reqwest::Error> {
-        self.client
+        playerid: &str,
+        command: &LmsCommand
+    ) -> LmsResult<reqwest::Response> {
+        let res = self.client
             .post(format!(
                 "http://{}/jsonrpc.js",
-                self.socket.to_string()
+                self.socket
             ))
             .json(&json!({
                 "method": "slim.request",
-                "params": command
+                "params": [playerid, command]
             }))
-            .send().await
+            .send().await?;
+
+        check_status(res)
     }
-}
 
-#[derive(Debug, Deserialize)]
-pub struct LmsResponse {
-    result: HashMap::<String, JsonValue>,
-}
+    pub async fn query<T: DeserializeOwned>(
+        &self,
+        playerid: &str,
+        command: &LmsCommand
+    ) -> LmsResult<T> {
+        let body = self.request(playerid, command).await?
+            .bytes().await?;
+        let reply: LmsReply<T> = serde_json::from_slice(&body)?;
 
-impl LmsResponse {
-    pub fn get_u64(&self, key: &str) -> Result<u64, String> {
-        if let Some(value) = self.result.get(key) {
-            if value.is_u64() {
-                Ok(value.as_u64().unwrap())
-            } else {
-                Err(format!("'{}' is not a u64!", key))
-            }
-        } else {
-            Err(format!("'{}' does not exist!", key))
-        }
+        Ok(reply.result)
     }
 
-    pub fn get_f64(&self, key: &str) -> Result<f64, String> {
-        if let Some(value) = self.result.get(key) {
-            if value.is_f64() {
-                Ok(value.as_f64().unwrap())
-            } else {
-                Err(format!("'{}' is not a f64!", key))
-            }
-        } else {
-            Err(format!("'{}' does not exist!", key))
-        }
-    }
+    pub async fn cometd(
+        &self,
+        messages: JsonValue,
+        timeout: Duration
+    ) -> LmsResult<Vec<JsonValue>> {
+        let res = self.client
+            .post(format!(
+                "http://{}/cometd",
+                self.socket
+            ))
+            .timeout(timeout)
+            .json(&messages)
+            .send().await?;
+        let body = check_status(res)?.bytes().await?;
 
-    pub fn get_str(&self, key: &str) -> Result<String, String> {
-        if let Some(value) = self.result.get(key) {
-            if value.is_string() {
-                Ok(value.as_str().unwrap().to_string())
-            } else {
-                Err(format!("'{}' is not a string!", key))
-            }
-        } else {
-            Err(format!("'{}' does not exist!", key))
-        }
+        Ok(serde_json::from_slice(&body)?)
     }
+}
 
-    pub fn get_array(
-        &self,
-        key: &str
-    ) -> Result<&Vec<JsonValue>, String> {
-        if let Some(value) = self.result.get(key) {
-            if value.is_array() {
-                Ok(value.as_array().unwrap())
-            } else {
-                Err(format!("'{}' is not an array!", key))
-            }
-        } else {
-            Err(format!("'{}' does not exist!", key))
-        }
+fn check_status(res: reqwest::Response) -> LmsResult<reqwest::Response> {
+    if res.status().is_success() {
+        Ok(res)
+    } else {
+        Err(LmsError::HttpStatus(res.status()))
     }
 }
 
+// Cometd delivers the result on its own, but JSON-RPC wraps it up first
+#[derive(Debug, Deserialize)]
+struct LmsReply<T> {
+    result: T,
+}
+
+// Library queries name their loop after whatever is being browsed, and
+// searches send one loop for each kind of result
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BrowseResponse {
+    #[serde(deserialize_with = "loose_u64")]
+    pub count: u64,
+    #[serde(rename = "artists_loop")]
+    pub artists: Vec<BrowseEntry>,
+    #[serde(rename = "albums_loop")]
+    pub albums: Vec<BrowseEntry>,
+    #[serde(rename = "genres_loop")]
+    pub genres: Vec<BrowseEntry>,
+    #[serde(rename = "years_loop")]
+    pub years: Vec<BrowseEntry>,
+    #[serde(rename = "titles_loop")]
+    pub titles: Vec<BrowseEntry>,
+    #[serde(rename = "playlists_loop")]
+    pub playlists: Vec<BrowseEntry>,
+    #[serde(rename = "playlisttracks_loop")]
+    pub playlist_tracks: Vec<BrowseEntry>,
+    // LMS really does double up the 's' on these two
+    #[serde(rename = "radioss_loop")]
+    pub radios: Vec<BrowseEntry>,
+    #[serde(rename = "appss_loop")]
+    pub apps: Vec<BrowseEntry>,
+    #[serde(rename = "loop_loop")]
+    pub items: Vec<BrowseEntry>,
+    #[serde(rename = "folder_loop")]
+    pub folder: Vec<BrowseEntry>,
+    #[serde(rename = "contributors_loop")]
+    pub contributors: Vec<BrowseEntry>,
+    #[serde(rename = "tracks_loop")]
+    pub tracks: Vec<BrowseEntry>,
+}
+
+// Each loop only fills in the handful of fields that make sense for it
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BrowseEntry {
+    #[serde(deserialize_with = "loose_string")]
+    pub id: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub name: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub title: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub artist: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub album: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub genre: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub year: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub playlist: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub filename: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub cmd: String,
+    #[serde(rename = "type", deserialize_with = "loose_string")]
+    pub kind: String,
+    #[serde(deserialize_with = "loose_flag")]
+    pub hasitems: bool,
+    #[serde(deserialize_with = "loose_flag")]
+    pub isaudio: bool,
+    // Search results name their ids after the kind of result
+    #[serde(deserialize_with = "loose_string")]
+    pub contributor: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub contributor_id: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub album_id: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub track: String,
+    #[serde(deserialize_with = "loose_string")]
+    pub track_id: String,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct LmsPlayer {
     pub name: String,
     pub playerid: String,
+    #[serde(default, deserialize_with = "loose_string")]
+    pub model: String,
+    #[serde(default, deserialize_with = "loose_string")]
+    pub ip: String,
+    #[serde(default = "default_flag", deserialize_with = "loose_flag")]
+    pub power: bool,
+    #[serde(default = "default_flag", deserialize_with = "loose_flag")]
+    pub connected: bool,
+    #[serde(default, deserialize_with = "loose_flag")]
+    pub isplaying: bool,
+    #[serde(default, deserialize_with = "loose_flag")]
+    pub canpoweroff: bool,
+    #[serde(default, deserialize_with = "loose_string")]
+    pub firmware: String,
+}
+
+impl LmsPlayer {
+    pub fn is_powered(&self) -> bool {
+        // Players that can't be switched off are always on
+        self.power || !self.canpoweroff
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.connected && self.is_powered()
+    }
+
+    pub fn address(&self) -> &str {
+        // The port is always the slimproto one, so it's just noise
+        self.ip.split(':').next().unwrap_or_default()
+    }
+}
+
+fn default_flag() -> bool {
+    true
+}
+
+// Depending on the server version and player type, LMS sends these as
+// numbers, strings or booleans
+fn loose_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match JsonValue::deserialize(deserializer)? {
+        JsonValue::Bool(b) => b,
+        JsonValue::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
+        JsonValue::String(s) => !matches!(s.as_str(), "" | "0"),
+        _ => false,
+    })
+}
+
+fn loose_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match JsonValue::deserialize(deserializer)? {
+        JsonValue::Number(n) => n.as_f64().unwrap_or(0.0) as u64,
+        JsonValue::String(s) => s.parse::<f64>().unwrap_or(0.0) as u64,
+        _ => 0,
+    })
+}
+
+fn loose_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match JsonValue::deserialize(deserializer)? {
+        JsonValue::Number(n) => n.as_f64().unwrap_or(0.0),
+        JsonValue::String(s) => s.parse::<f64>().unwrap_or(0.0),
+        _ => 0.0,
+    })
+}
+
+fn loose_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match JsonValue::deserialize(deserializer)? {
+        JsonValue::String(s) => s,
+        JsonValue::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LmsAlarm {
+    #[serde(deserialize_with = "loose_string")]
+    pub id: String,
+    #[serde(default, deserialize_with = "loose_string")]
+    pub dow: String,
+    #[serde(default, deserialize_with = "loose_flag")]
+    pub enabled: bool,
+    #[serde(default, deserialize_with = "loose_flag")]
+    pub repeat: bool,
+    #[serde(default, deserialize_with = "loose_u64")]
+    pub time: u64,
+    #[serde(default, deserialize_with = "loose_u64")]
+    pub volume: u64,
+    #[serde(default, deserialize_with = "loose_string")]
+    pub url: String,
+}
+
+impl LmsAlarm {
+    pub fn days(&self) -> Vec<u64> {
+        // Days of the week are numbered from Sunday, starting at 0
+        let mut days: Vec<u64> = self.dow
+            .split(',')
+            .filter_map(|day| day.trim().parse::<u64>().ok())
+            .filter(|day| *day < 7)
+            .collect();
+        days.sort();
+        days.dedup();
+
+        days
+    }
+
+    pub fn plays_current_playlist(&self) -> bool {
+        matches!(self.url.as_str(), "" | "0" | "CURRENT_PLAYLIST")
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum PlaylistMode {
     STOP,
     PLAY,
@@ -115,12 +357,15 @@ pub enum PlaylistMode {
 }
 
 impl PlaylistMode {
-    pub fn from(text: &str) -> Self {
+    pub fn from(text: &str) -> LmsResult<Self> {
         match text {
-            "play" => PlaylistMode::PLAY,
-            "stop" => PlaylistMode::STOP,
-            "pause" => PlaylistMode::PAUSE,
-            _ => unreachable!()
+            "play" => Ok(PlaylistMode::PLAY),
+            "stop" => Ok(PlaylistMode::STOP),
+            "pause" => Ok(PlaylistMode::PAUSE),
+            _ => Err(LmsError::UnknownValue {
+                field: "mode".to_string(),
+                value: text.to_string(),
+            }),
         }
     }
 }
@@ -135,7 +380,8 @@ impl fmt::Display for PlaylistMode {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum RepeatMode {
     NONE,
     TRACK,
@@ -150,6 +396,22 @@ impl RepeatMode {
             _ => RepeatMode::NONE,
         }
     }
+
+    pub fn value(&self) -> u64 {
+        match self {
+            RepeatMode::NONE => 0,
+            RepeatMode::TRACK => 1,
+            RepeatMode::PLAYLIST => 2,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            RepeatMode::NONE => RepeatMode::TRACK,
+            RepeatMode::TRACK => RepeatMode::PLAYLIST,
+            RepeatMode::PLAYLIST => RepeatMode::NONE,
+        }
+    }
 }
 
 impl fmt::Display for RepeatMode {
@@ -162,7 +424,8 @@ impl fmt::Display for RepeatMode {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum ShuffleMode {
     NONE,
     TRACK,
@@ -177,6 +440,22 @@ impl ShuffleMode {
             _ => ShuffleMode::NONE,
         }
     }
+
+    pub fn value(&self) -> u64 {
+        match self {
+            ShuffleMode::NONE => 0,
+            ShuffleMode::TRACK => 1,
+            ShuffleMode::ALBUM => 2,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ShuffleMode::NONE => ShuffleMode::TRACK,
+            ShuffleMode::TRACK => ShuffleMode::ALBUM,
+            ShuffleMode::ALBUM => ShuffleMode::NONE,
+        }
+    }
 }
 
 impl fmt::Display for ShuffleMode {
@@ -189,6 +468,77 @@ impl fmt::Display for ShuffleMode {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ServerStatusResponse {
+    #[serde(rename = "players_loop", default)]
+    pub players: Vec<LmsPlayer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusResponse {
+    pub player_name: String,
+    #[serde(rename = "playlist_tracks", default, deserialize_with = "loose_u64")]
+    pub total_tracks: u64,
+    #[serde(rename = "playlist_cur_index", default, deserialize_with = "loose_u64")]
+    pub playlist_index: u64,
+    #[serde(rename = "playlist repeat", default, deserialize_with = "loose_u64")]
+    pub repeat: u64,
+    #[serde(rename = "playlist shuffle", default, deserialize_with = "loose_u64")]
+    pub shuffle: u64,
+    // Players that have been switched off don't report much at all
+    pub mode: Option<String>,
+    #[serde(rename = "mixer volume", default, deserialize_with = "loose_f64")]
+    pub mixer_volume: f64,
+    #[serde(default, deserialize_with = "loose_f64")]
+    pub time: f64,
+    #[serde(default = "default_flag", deserialize_with = "loose_flag")]
+    pub power: bool,
+    pub sync_master: Option<String>,
+    pub sync_slaves: Option<String>,
+    pub will_sleep_in: Option<f64>,
+    pub playlist_timestamp: Option<f64>,
+    #[serde(rename = "playlist_loop", default)]
+    pub tracks: Vec<LmsSong>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeResponse {
+    #[serde(rename = "_time", deserialize_with = "loose_f64")]
+    pub time: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncGroupsResponse {
+    #[serde(rename = "syncgroups_loop", default)]
+    pub groups: Vec<LmsSyncGroup>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LmsSyncGroup {
+    #[serde(rename = "sync_members", deserialize_with = "loose_string")]
+    pub members: String,
+    #[serde(rename = "sync_member_names", default, deserialize_with = "loose_string")]
+    pub names: String,
+}
+
+impl LmsSyncGroup {
+    pub fn contains(&self, playerid: &str) -> bool {
+        self.members.split(',').any(|member| member == playerid)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlarmsResponse {
+    #[serde(rename = "alarms_loop", default)]
+    pub alarms: Vec<LmsAlarm>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlayerPrefResponse {
+    #[serde(rename = "_p2", default, deserialize_with = "loose_string")]
+    pub value: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct LmsStatus {
     pub player_name: String,
@@ -198,9 +548,78 @@ pub struct LmsStatus {
     pub playlist_mode: PlaylistMode,
     pub total_tracks: u64,
     pub elapsed_duration: f64,
+    pub volume: u64,
+    pub muted: bool,
+    pub power: bool,
+    pub sync_master: Option<String>,
+    pub sync_slaves: Vec<String>,
+    pub will_sleep_in: Option<f64>,
+    pub playlist_timestamp: Option<f64>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+impl LmsStatus {
+    pub fn from_response(res: StatusResponse) -> LmsResult<Self> {
+        let total_tracks = res.total_tracks;
+        let playlist_index = if total_tracks == 0 {
+            0
+        } else {
+            res.playlist_index
+        };
+        // Players that are switched off leave the mode out, which is fine,
+        // but a mode we don't know about is not
+        let playlist_mode = PlaylistMode::from(
+            res.mode.as_deref().unwrap_or("stop")
+        )?;
+        // LMS reports a negative volume while the player is muted
+        let mixer_volume = res.mixer_volume as i64;
+
+        // Both of these are only present while the player is synced
+        let sync_slaves = res.sync_slaves
+            .map(|slaves| {
+                slaves.split(',').map(|s| s.to_string()).collect()
+            })
+            .unwrap_or_default();
+
+        // The status response carries the elapsed time too, but callers that
+        // need it to be exact can overwrite it with a 'time ?' query
+        let elapsed_duration = if total_tracks == 0
+            || playlist_mode == PlaylistMode::STOP
+        {
+            0.0
+        } else {
+            res.time
+        };
+
+        Ok(Self {
+            player_name: res.player_name,
+            playlist_index,
+            playlist_repeat: RepeatMode::from(res.repeat),
+            playlist_shuffle: ShuffleMode::from(res.shuffle),
+            playlist_mode,
+            total_tracks,
+            elapsed_duration,
+            volume: mixer_volume.unsigned_abs(),
+            muted: mixer_volume < 0,
+            power: res.power,
+            sync_master: res.sync_master,
+            sync_slaves,
+            will_sleep_in: res.will_sleep_in,
+            playlist_timestamp: res.playlist_timestamp,
+        })
+    }
+
+    pub fn sync_members(&self) -> Vec<String> {
+        let mut members: Vec<String> = self.sync_master
+            .iter()
+            .cloned()
+            .collect();
+        members.extend(self.sync_slaves.iter().cloned());
+
+        members
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct LmsPlaylist {
     pub tracks: Vec<LmsSong>,
 }
@@ -215,20 +634,238 @@ impl LmsPlaylist {
 pub struct LmsSong {
     #[serde(rename = "playlist index")]
     pub index: u64,
+    #[serde(default)]
+    pub id: i64,
     pub title: String,
+    #[serde(default)]
     pub artist: String,
+    #[serde(default)]
     pub album: String,
+    #[serde(default)]
     pub duration: f64,
+    #[serde(default)]
+    pub url: String,
 }
 
 impl LmsSong {
     pub fn default() -> Self {
         Self {
             index: 0,
+            id: 0,
             title: String::new(),
             artist: String::new(),
             album: String::new(),
             duration: 0.1,
+            url: String::new(),
         }
     }
+
+    pub fn is_seekable(&self) -> bool {
+        self.duration > 0.0
+    }
+
+    // Library tracks are added by id and remote ones by url
+    pub fn can_be_added(&self) -> bool {
+        self.id > 0 || !self.url.is_empty()
+    }
+
+    // Tests only care about what a track would be queued by
+    #[cfg(test)]
+    pub fn with_source(id: i64, url: &str) -> Self {
+        Self {
+            id,
+            url: url.to_string(),
+            ..LmsSong::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<T: DeserializeOwned>(body: &str) -> T {
+        serde_json::from_str::<LmsReply<T>>(body).unwrap().result
+    }
+
+    #[test]
+    fn parses_player_status() {
+        let res: StatusResponse = parse(r#"{
+            "id": 1,
+            "method": "slim.request",
+            "params": ["aa:bb:cc:dd:ee:ff", ["status", "-", 1, "tags:adl"]],
+            "result": {
+                "player_name": "Kitchen",
+                "player_connected": 1,
+                "power": 1,
+                "mode": "play",
+                "time": 83.5,
+                "mixer volume": -40,
+                "playlist repeat": 2,
+                "playlist shuffle": 0,
+                "playlist_timestamp": 1712345678.123,
+                "playlist_cur_index": "2",
+                "playlist_tracks": 12,
+                "sync_master": "aa:bb:cc:dd:ee:ff",
+                "sync_slaves": "11:22:33:44:55:66,77:88:99:aa:bb:cc",
+                "will_sleep_in": 600,
+                "playlist_loop": [{
+                    "playlist index": 2,
+                    "id": 7,
+                    "title": "Blue in Green",
+                    "artist": "Miles Davis",
+                    "album": "Kind of Blue",
+                    "duration": 337.8
+                }]
+            }
+        }"#);
+
+        let track = res.tracks.first().cloned().unwrap();
+        assert_eq!(track.title, "Blue in Green");
+        assert_eq!(track.index, 2);
+
+        let status = LmsStatus::from_response(res).unwrap();
+        assert_eq!(status.player_name, "Kitchen");
+        assert_eq!(status.playlist_index, 2);
+        assert_eq!(status.total_tracks, 12);
+        assert_eq!(status.playlist_mode, PlaylistMode::PLAY);
+        assert_eq!(status.playlist_repeat, RepeatMode::PLAYLIST);
+        assert_eq!(status.playlist_shuffle, ShuffleMode::NONE);
+        assert_eq!(status.elapsed_duration, 83.5);
+        assert_eq!(status.volume, 40);
+        assert!(status.muted);
+        assert!(status.power);
+        assert_eq!(status.will_sleep_in, Some(600.0));
+        assert_eq!(status.sync_members().len(), 3);
+    }
+
+    #[test]
+    fn parses_powered_off_status() {
+        let res: StatusResponse = parse(r#"{
+            "result": { "player_name": "Office", "power": 0 }
+        }"#);
+        assert!(res.mode.is_none());
+
+        let status = LmsStatus::from_response(res).unwrap();
+        assert!(!status.power);
+        assert_eq!(status.playlist_mode, PlaylistMode::STOP);
+        assert_eq!(status.total_tracks, 0);
+        assert_eq!(status.elapsed_duration, 0.0);
+        assert!(status.sync_members().is_empty());
+    }
+
+    #[test]
+    fn rejects_odd_status() {
+        let res: StatusResponse = parse(r#"{
+            "result": { "player_name": "Office", "mode": "rewind" }
+        }"#);
+        assert!(matches!(
+            LmsStatus::from_response(res),
+            Err(LmsError::UnknownValue { value, .. }) if value == "rewind"
+        ));
+
+        let res = serde_json::from_str::<LmsReply<StatusResponse>>(r#"{
+            "result": { "mode": "play" }
+        }"#);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn parses_browse_loops() {
+        let res: BrowseResponse = parse(r#"{
+            "result": {
+                "count": "12",
+                "loop_loop": [
+                    { "id": "4a1.0", "name": "Jazz", "hasitems": 1 },
+                    { "id": 17, "name": "Search", "type": "search" },
+                    { "id": "4a1.2", "name": "KCSM", "isaudio": "1" }
+                ]
+            }
+        }"#);
+
+        assert_eq!(res.count, 12);
+        assert!(res.artists.is_empty());
+        assert_eq!(res.items.len(), 3);
+        assert!(res.items[0].hasitems && !res.items[0].isaudio);
+        assert_eq!(res.items[1].id, "17");
+        assert_eq!(res.items[1].kind, "search");
+        assert!(res.items[2].isaudio && !res.items[2].hasitems);
+    }
+
+    #[test]
+    fn parses_server_status() {
+        let res: ServerStatusResponse = parse(r#"{
+            "result": {
+                "player count": 2,
+                "players_loop": [
+                    {
+                        "name": "Kitchen",
+                        "playerid": "aa:bb:cc:dd:ee:ff",
+                        "model": "squeezelite",
+                        "ip": "192.168.1.20:41234",
+                        "power": 1,
+                        "connected": 1,
+                        "isplaying": 0,
+                        "canpoweroff": 1,
+                        "firmware": "v1.9.9"
+                    },
+                    {
+                        "name": "Office",
+                        "playerid": "11:22:33:44:55:66",
+                        "power": "0",
+                        "canpoweroff": 1
+                    }
+                ]
+            }
+        }"#);
+
+        assert_eq!(res.players.len(), 2);
+        assert_eq!(res.players[0].address(), "192.168.1.20");
+        assert!(res.players[0].is_available());
+        assert!(!res.players[1].is_powered());
+
+        let res: ServerStatusResponse = parse(r#"{
+            "result": { "player count": 0 }
+        }"#);
+        assert!(res.players.is_empty());
+    }
+
+    #[test]
+    fn parses_small_replies() {
+        let res: TimeResponse = parse(r#"{ "result": { "_time": "41.2" } }"#);
+        assert_eq!(res.time, 41.2);
+
+        let res: PlayerPrefResponse = parse(r#"{ "result": { "_p2": 1 } }"#);
+        assert_eq!(res.value, "1");
+
+        let res: SyncGroupsResponse = parse(r#"{
+            "result": {
+                "syncgroups_loop": [{
+                    "sync_members": "aa:bb:cc:dd:ee:ff,11:22:33:44:55:66",
+                    "sync_member_names": "Kitchen,Office"
+                }]
+            }
+        }"#);
+        assert_eq!(res.groups[0].names, "Kitchen,Office");
+        assert!(res.groups[0].contains("11:22:33:44:55:66"));
+        assert!(!res.groups[0].contains("11:22:33"));
+
+        let res: AlarmsResponse = parse(r#"{
+            "result": {
+                "count": 1,
+                "alarms_loop": [{
+                    "id": "8f2c1a",
+                    "dow": "1,2,3,4,5",
+                    "enabled": "1",
+                    "repeat": "1",
+                    "time": "25200",
+                    "volume": "50",
+                    "url": "CURRENT_PLAYLIST"
+                }]
+            }
+        }"#);
+        assert_eq!(res.alarms[0].time, 25200);
+        assert_eq!(res.alarms[0].days(), vec![1, 2, 3, 4, 5]);
+        assert!(res.alarms[0].plays_current_playlist());
+    }
 }
@@ -1,3 +1,4 @@
+
 use std::{
     env,
     error::Error,
@@ -10,9 +11,15 @@ use ratatui::{
     Terminal,
 };
 
+mod alarms;
 mod app;
+mod browser;
+mod cli;
+mod cometd;
+mod command;
 mod config;
 mod events;
+mod history;
 mod lms;
 mod tui_handling;
 mod ui;
@@ -20,6 +27,7 @@ mod ui;
 use app::*;
 use config::*;
 use events::*;
+use lms::*;
 use tui_handling::*;
 use ui::*;
 
@@ -76,7 +84,8 @@ async fn run_app<B: Backend>(
     mut app: App,
     tick_rate: Duration,
 ) -> DynResult<()> {
-    app.on_tick().await?;
+    let res = app.on_tick().await;
+    app.report(res);
     let mut last_tick = Instant::now();
 
     loop {
@@ -86,14 +95,28 @@ async fn run_app<B: Backend>(
 
         terminal.draw(|f| ui(f, &mut app))?;
 
-        let timeout = tick_rate
+        let mut timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
+        if app.search.is_pending() || app.count_is_pending() {
+            // Wake up often enough to notice when typing has paused
+            timeout = timeout.min(Duration::from_millis(50));
+        }
         if crossterm::event::poll(timeout)? {
-            handle_events(&mut app).await?;
+            if let Err(err) = handle_events(&mut app).await {
+                // Anything other than a server error means the terminal
+                // itself is in trouble, so there's no point carrying on
+                let err = err.downcast::<LmsError>()?;
+                app.report(Err(*err));
+            }
         }
+        let res = app.run_pending_search().await;
+        app.report(res);
+        let res = app.run_pending_count().await;
+        app.report(res);
         if last_tick.elapsed() >= tick_rate {
-            app.on_tick().await?;
+            let res = app.on_tick().await;
+            app.report(res);
             last_tick = Instant::now();
         }
     }
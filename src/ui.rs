@@ -13,10 +13,12 @@ use ratatui::{
     text::{Line, Span},
     widgets::{
         Block,
+        Borders,
         Clear,
         Gauge,
         List,
         ListItem,
+        ListState,
         Paragraph,
         Widget,
         Wrap
@@ -25,21 +27,22 @@ use ratatui::{
 };
 use unicode_truncate::UnicodeTruncateStr;
 
+use crate::alarms::*;
 use crate::app::*;
+use crate::browser::*;
+use crate::config::*;
 use crate::lms::*;
 
 macro_rules! raw_para {
     ( $( $x:expr ),* ) => {
         {
-            let mut temp_para = Vec::new();
-            $(
-                temp_para.push(
+            vec![
+                $(
                     Line::from(
                         Span::raw($x)
-                    )
-                );
-            )*
-            temp_para
+                    ),
+                )*
+            ]
         }
     };
 }
@@ -105,10 +108,277 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         return;
     }
 
+    // The view we were on stays as it was underneath, ready for when the
+    // server comes back
+    if let Some(disconnected) = &app.disconnected {
+        render_disconnected_state(f, disconnected, app);
+        return;
+    }
+
     match app.state {
         AppState::PlayerMenu => render_player_menu_state(f, app),
+        AppState::Dashboard => render_dashboard_state(f, app),
         AppState::Playlist => render_playlist_state(f, app),
+        AppState::Library => render_browser_state(f, app),
+        AppState::Search => render_browser_state(f, app),
+        AppState::SavedPlaylists => render_browser_state(f, app),
+        AppState::Favorites => render_browser_state(f, app),
+        AppState::Apps => render_browser_state(f, app),
+        AppState::MusicFolder => render_browser_state(f, app),
+        AppState::Alarms => render_alarms_state(f, app),
+    }
+
+    if let Some(popup) = &app.popup {
+        render_popup(f, popup);
+    }
+
+    if let Some(error) = &app.error {
+        render_error_popup(f, &error.to_string());
+    }
+}
+
+fn render_popup(f: &mut Frame, popup: &Popup) {
+    match popup {
+        Popup::JumpToTrack(number) => render_input_popup(
+            f,
+            "Play Track",
+            "Track number:",
+            number
+        ),
+        Popup::ConfirmClear => render_confirm_popup(
+            f,
+            "Clear Playlist",
+            "Remove every track from the playlist?"
+        ),
+        Popup::SavePlaylist(name) => render_input_popup(
+            f,
+            "Save Playlist",
+            "Save the current playlist as:",
+            name
+        ),
+        Popup::ConfirmDeletePlaylist { name, .. } => render_confirm_popup(
+            f,
+            "Delete Playlist",
+            &format!("Delete the saved playlist '{}'?", name)
+        ),
+        Popup::ConfirmDeleteFavorite { name, .. } => render_confirm_popup(
+            f,
+            "Remove Favorite",
+            &format!("Remove '{}' from your favorites?", name)
+        ),
+        Popup::BrowseInput { title, text, .. } => render_input_popup(
+            f,
+            title,
+            "Search for:",
+            text
+        ),
+        Popup::SyncWith { targets, selected, .. } => render_list_popup(
+            f,
+            "Sync With",
+            "No other players to sync with.",
+            targets.iter().map(|t| t.name.clone()).collect(),
+            *selected
+        ),
+        Popup::TransferTo { targets, selected } => render_list_popup(
+            f,
+            "Transfer To (Enter: move, c: copy)",
+            "No other players to transfer to.",
+            targets.iter().map(|t| t.name.clone()).collect(),
+            *selected
+        ),
+        Popup::AlarmForm(form) => render_alarm_form_popup(f, form),
+        Popup::ConfirmDeleteAlarm { time, .. } => render_confirm_popup(
+            f,
+            "Delete Alarm",
+            &format!("Delete the alarm at {}?", time)
+        ),
+        Popup::SleepTimer(selected) => render_list_popup(
+            f,
+            "Sleep Timer",
+            "",
+            SleepTimer::options().iter().map(|o| o.to_string()).collect(),
+            *selected
+        ),
+    }
+}
+
+fn render_list_popup(
+    f: &mut Frame,
+    title: &str,
+    empty_text: &str,
+    items: Vec<String>,
+    selected: usize
+) {
+    let area = popup_rect(f.size(), std::cmp::max(items.len() as u16, 1) + 2);
+
+    render_popup_frame(f, area, title);
+
+    let list_area = shrink_rect(area, 1);
+    if items.is_empty() {
+        let text = Paragraph::new(raw_para!(empty_text.to_string()))
+            .block(Block::default())
+            .alignment(Alignment::Center);
+
+        f.render_widget(text, list_area);
+        return;
     }
+
+    let items: Vec<ListItem> = items
+        .into_iter()
+        .map(|item| ListItem::new(Span::raw(item)))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+
+    f.render_stateful_widget(list, list_area, &mut state);
+}
+
+fn render_confirm_popup(f: &mut Frame, title: &str, question: &str) {
+    let area = popup_rect(f.size(), 5);
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(
+                question.to_string(),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("(y)es / (n)o"),
+        ]),
+    ];
+
+    render_popup_frame(f, area, title);
+
+    let text = Paragraph::new(text)
+        .block(Block::default())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(text, shrink_rect(area, 1));
+}
+
+fn render_input_popup(
+    f: &mut Frame,
+    title: &str,
+    prompt: &str,
+    input: &str
+) {
+    let area = popup_rect(f.size(), 5);
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(
+                prompt.to_string(),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw(format!("{}_", input)),
+        ]),
+    ];
+
+    render_popup_frame(f, area, title);
+
+    let text = Paragraph::new(text)
+        .block(Block::default())
+        .alignment(Alignment::Center);
+
+    f.render_widget(text, shrink_rect(area, 1));
+}
+
+fn render_alarm_form_popup(f: &mut Frame, form: &AlarmForm) {
+    let area = popup_rect(f.size(), ALARM_FIELDS.len() as u16 + 4);
+    let title = if form.id.is_some() { "Edit Alarm" } else { "New Alarm" };
+
+    let mut text: Vec<Line> = ALARM_FIELDS
+        .iter()
+        .zip(form.fields.iter())
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let (value, style) = if i == form.focus {
+                (
+                    format!("{}_", value),
+                    Style::default().add_modifier(Modifier::BOLD)
+                )
+            } else {
+                (value.clone(), Style::default())
+            };
+
+            Line::from(vec![
+                Span::styled(format!("{}: ", label), style),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+    text.push(Line::from(""));
+    text.push(Line::from(vec![
+        Span::styled(
+            "Tab: next field | Enter: save | Esc: cancel",
+            Style::default().add_modifier(Modifier::DIM)
+        ),
+    ]));
+
+    render_popup_frame(f, area, title);
+
+    let text = Paragraph::new(text)
+        .block(Block::default());
+
+    f.render_widget(text, shrink_rect(area, 1));
+}
+
+fn render_error_popup(f: &mut Frame, message: &str) {
+    // Leave enough room for long messages to wrap onto a few lines
+    let area = popup_rect(f.size(), 7);
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(
+                message.to_string(),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "Press any key to continue",
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+        ]),
+    ];
+
+    render_popup_frame(f, area, "Error");
+
+    let text = Paragraph::new(text)
+        .block(Block::default())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(text, shrink_rect(area, 1));
+}
+
+fn render_popup_frame(f: &mut Frame, area: Rect, title: &str) {
+    f.render_widget(Clear, area);
+
+    let container = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", title))
+        .title_alignment(Alignment::Center);
+
+    f.render_widget(container, area);
+}
+
+fn popup_rect(size: Rect, height: u16) -> Rect {
+    // Popups keep a fixed height, centered within the frame
+    let area = centered_rect(50, 100, size);
+    let height = height.min(area.height);
+    let y = area.y + (area.height - height) / 2;
+
+    Rect { y, height, ..area }
 }
 
 fn render_player_menu_state(
@@ -130,7 +400,7 @@ fn render_player_menu_state(
 
         render_banner(f, chunks[0], app);
 
-        let list_area = centered_rect(40, 100, chunks[1]);
+        let list_area = centered_rect(70, 100, chunks[1]);
 
         if app.player_list.is_empty() {
             render_empty_list_info(f, list_area);
@@ -154,7 +424,7 @@ fn render_player_menu_state(
 
         render_tiny_banner(f, chunks[0], app);
 
-        let list_area = centered_rect(40, 100, chunks[1]);
+        let list_area = centered_rect(70, 100, chunks[1]);
 
         if app.player_list.is_empty() {
             render_empty_list_info(f, list_area);
@@ -166,6 +436,113 @@ fn render_player_menu_state(
     }
 }
 
+fn render_disconnected_state(
+    f: &mut Frame,
+    disconnected: &Disconnected,
+    app: &App
+) {
+    let chunks = if f.size().height > 15 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                Constraint::Length(10),
+                Constraint::Min(3),
+                Constraint::Length(3),
+                ]
+                .as_ref()
+            )
+            .split(f.size());
+
+        render_banner(f, chunks[0], app);
+
+        chunks
+    } else {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                Constraint::Length(5),
+                Constraint::Min(3),
+                Constraint::Length(1),
+                ]
+                .as_ref()
+            )
+            .split(f.size());
+
+        render_tiny_banner(f, chunks[0], app);
+
+        chunks
+    };
+
+    let info_area = centered_rect(70, 100, chunks[1]);
+    render_disconnected_info(f, info_area, disconnected, app);
+
+    render_disconnected_footer(f, chunks[2]);
+}
+
+fn render_disconnected_info(
+    f: &mut Frame,
+    chunk: Rect,
+    disconnected: &Disconnected,
+    app: &App
+) {
+    let container = CustomBorder::new()
+        .title("Disconnected".to_string());
+
+    f.render_widget(container, chunk);
+
+    let retry_in = disconnected.retry_in().as_secs_f64().ceil() as u64;
+    let retry = if retry_in == 0 {
+        "Reconnecting...".to_string()
+    } else {
+        format!(
+            "Retrying in {}s (attempt {})",
+            retry_in,
+            disconnected.attempts + 1
+        )
+    };
+
+    let info = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                format!("Waiting for the server at {}", app.server_address()),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                disconnected.error.clone(),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::raw(retry)),
+    ];
+
+    let info = Paragraph::new(info)
+        .block(Block::default())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(info, shrink_rect(chunk, 1));
+}
+
+fn render_disconnected_footer(f: &mut Frame, chunk: Rect) {
+    let mut info = raw_para!("r: retry now | q: quit");
+    if chunk.height > 1 {
+        info.insert(0, Line::from(""));
+    }
+
+    let info = Paragraph::new(info)
+        .block(Block::default())
+        .alignment(Alignment::Center);
+
+    f.render_widget(info, chunk);
+}
+
 fn render_banner(
     f: &mut Frame,
     chunk: Rect,
@@ -262,7 +639,7 @@ fn render_player_list(
         .iter()
         .map(|p| {
             ListItem::new(
-                Span::raw(p.name.clone())
+                player_span(p, list_area.width, app)
             )
         })
         .collect();
@@ -294,6 +671,263 @@ fn render_player_menu_footer(
     f.render_widget(info, chunk);
 }
 
+const DASHBOARD_CARD_WIDTH: u16 = 34;
+const DASHBOARD_CARD_HEIGHT: u16 = 6;
+
+fn render_dashboard_state(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(2),
+            ]
+            .as_ref()
+        )
+        .split(f.size());
+
+    render_dashboard_header(f, chunks[0], app);
+    if app.player_list.is_empty() {
+        render_empty_list_info(f, centered_rect(40, 100, chunks[1]));
+    } else {
+        render_dashboard_cards(f, chunks[1], app);
+    }
+    render_dashboard_footer(f, chunks[2]);
+}
+
+fn render_dashboard_header(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &App
+) {
+    let left = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "Dashboard",
+            Style::default().add_modifier(Modifier::BOLD)
+        ),
+    ]))
+    .block(Block::default());
+
+    let playing = app.dashboard
+        .iter()
+        .flatten()
+        .filter(|card| card.status.playlist_mode == PlaylistMode::PLAY)
+        .count();
+    let right = Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!(
+                "{} Players | {} Playing",
+                app.player_list.players.len(),
+                playing
+            ),
+            Style::default().add_modifier(Modifier::BOLD)
+        ),
+    ]))
+    .block(Block::default())
+    .alignment(Alignment::Right);
+
+    f.render_widget(left, chunk);
+    f.render_widget(right, chunk);
+    render_status_bar(f, chunk);
+}
+
+fn render_dashboard_cards(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &mut App
+) {
+    let columns = std::cmp::max(chunk.width / DASHBOARD_CARD_WIDTH, 1);
+    let rows = std::cmp::max(chunk.height / DASHBOARD_CARD_HEIGHT, 1);
+    app.dashboard_columns = columns as usize;
+
+    // Scroll a row at a time so that the selected card is always visible
+    let selected = app.player_list.state.selected().unwrap_or(0);
+    let selected_row = (selected / columns as usize) as u16;
+    let first_row = selected_row.saturating_sub(rows - 1);
+
+    let card_width = chunk.width / columns;
+    for (i, player) in app.player_list.players.iter().enumerate() {
+        let row = (i / columns as usize) as u16;
+        if row < first_row || row >= first_row + rows {
+            continue;
+        }
+
+        let column = (i % columns as usize) as u16;
+        let area = Rect {
+            x: chunk.x + column * card_width,
+            y: chunk.y + (row - first_row) * DASHBOARD_CARD_HEIGHT,
+            width: card_width,
+            height: std::cmp::min(DASHBOARD_CARD_HEIGHT, chunk.height),
+        };
+
+        let card = app.dashboard.get(i).and_then(|card| card.as_ref());
+        render_dashboard_card(f, area, player, card, i == selected, app);
+    }
+}
+
+fn render_dashboard_card(
+    f: &mut Frame,
+    area: Rect,
+    player: &LmsPlayer,
+    card: Option<&PlayerCard>,
+    selected: bool,
+    app: &App
+) {
+    let border_style = if selected {
+        Style::default()
+        .fg(Color::Indexed(*app.config.color("PlayerName")))
+        .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().add_modifier(Modifier::DIM)
+    };
+    let container = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border_style)
+        .title(Span::styled(
+            format!(" {} ", player.name),
+            Style::default().add_modifier(Modifier::BOLD)
+        ));
+
+    f.render_widget(container, area);
+
+    let inner = shrink_rect(area, 1);
+    if inner.height < 4 || inner.width < 10 {
+        return;
+    }
+    let width = inner.width as usize;
+
+    let (status, track) = match card {
+        Some(card) => (&card.status, card.track.as_ref()),
+        None => {
+            let info = Paragraph::new(raw_para!("", "Not responding"))
+                .block(Block::default())
+                .style(Style::default().add_modifier(Modifier::DIM))
+                .alignment(Alignment::Center);
+
+            f.render_widget(info, inner);
+            return;
+        },
+    };
+
+    let (title, artist, duration) = match track {
+        Some(track) if status.total_tracks != 0 => (
+            track.title.unicode_truncate(width).0.to_string(),
+            track.artist.unicode_truncate(width).0.to_string(),
+            track.duration,
+        ),
+        _ => ("Nothing queued".to_string(), String::new(), 0.0),
+    };
+
+    let mode_color = match status.playlist_mode {
+        PlaylistMode::STOP =>
+            Color::Indexed(*app.config.color("StoppedIndicator")),
+        PlaylistMode::PLAY =>
+            Color::Indexed(*app.config.color("PlayingIndicator")),
+        PlaylistMode::PAUSE =>
+            Color::Indexed(*app.config.color("PausedIndicator")),
+    };
+    let (mode, mode_color) = if status.power {
+        (status.playlist_mode.to_string(), mode_color)
+    } else {
+        (
+            "OFF".to_string(),
+            Color::Indexed(*app.config.color("StoppedIndicator"))
+        )
+    };
+
+    let mut info = vec![
+        Span::styled(
+            mode,
+            Style::default()
+            .fg(mode_color)
+            .add_modifier(Modifier::BOLD)
+        ),
+        Span::raw(" | "),
+    ];
+    info.append(&mut volume_spans(status, inner.width, app));
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(
+                title,
+                Style::default()
+                .fg(Color::Indexed(*app.config.color("TrackTitle")))
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                artist,
+                Style::default()
+                .fg(Color::Indexed(*app.config.color("TrackArtist")))
+            ),
+        ]),
+        Line::from(info),
+    ];
+
+    let text = Paragraph::new(text)
+        .block(Block::default());
+
+    f.render_widget(text, inner);
+
+    let times = format!(
+        "{}/{}",
+        format_time(status.elapsed_duration, false),
+        format_time(duration, false)
+    );
+    let times_area = Rect {
+        y: inner.y + 2,
+        height: 1,
+        ..inner
+    };
+    let times = Paragraph::new(Line::from(vec![Span::raw(times)]))
+        .block(Block::default())
+        .alignment(Alignment::Right);
+
+    f.render_widget(times, times_area);
+
+    let playbar = Gauge::default()
+        .block(Block::default())
+        .gauge_style(
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlaybarGauge")))
+        )
+        .ratio(playbar_ratio(status.elapsed_duration, duration))
+        .label("");
+    let playbar_area = Rect {
+        y: inner.y + 3,
+        height: 1,
+        ..inner
+    };
+
+    f.render_widget(playbar, playbar_area);
+}
+
+fn render_dashboard_footer(f: &mut Frame, chunk: Rect) {
+    let hints = [
+        "Enter: open playlist",
+        "hjkl: move",
+        "Tab: player menu",
+    ];
+
+    let footer = vec![
+        Line::from(vec![
+            Span::raw(construct_bar(chunk.width)),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                hints.join(" | "),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+        ]),
+    ];
+
+    let footer = Paragraph::new(footer)
+        .block(Block::default());
+
+    f.render_widget(footer, chunk);
+}
+
 fn render_playlist_state(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -309,7 +943,7 @@ fn render_playlist_state(f: &mut Frame, app: &mut App) {
 
     render_status_header(f, chunks[0], app);
     render_playlist(f, chunks[1], app);
-    render_playbar_footer(f, chunks[2], app);
+    app.playbar_area = render_playbar_footer(f, chunks[2], app);
 }
 
 fn render_status_header(
@@ -333,7 +967,7 @@ fn render_status_info_left(
     status: &LmsStatus,
     app: &App
 ) {
-    let left = Line::from(vec![
+    let mut left = vec![
         Span::styled(
             "Player: ",
             Style::default().add_modifier(Modifier::BOLD)
@@ -343,7 +977,22 @@ fn render_status_info_left(
             Style::default()
             .fg(Color::Indexed(*app.config.color("PlayerName")))
         ),
-    ]);
+    ];
+
+    let playerid = app.get_current_playerid().unwrap_or_default();
+    let group: Vec<String> = status.sync_members()
+        .iter()
+        .filter(|id| **id != playerid)
+        .map(|id| app.player_name(id))
+        .collect();
+    if !group.is_empty() {
+        left.push(Span::styled(
+            format!(" + {}", group.join(", ")),
+            Style::default().add_modifier(Modifier::DIM)
+        ));
+    }
+
+    let left = Line::from(left);
 
     let left = Paragraph::new(left)
         .block(Block::default());
@@ -368,7 +1017,7 @@ fn render_status_info_center(
     }
     let playlist_duration = format_time(playlist_duration, false);
 
-    let center = Line::from(vec![
+    let mut center = vec![
         Span::styled(
             num_tracks,
             Style::default().add_modifier(Modifier::BOLD)
@@ -378,7 +1027,31 @@ fn render_status_info_center(
             playlist_duration,
             Style::default().add_modifier(Modifier::BOLD)
         ),
-    ]);
+    ];
+
+    // The right side is already crowded, so the countdown lives here
+    let sleep = status.will_sleep_in.unwrap_or(0.0);
+    if sleep > 0.0 {
+        center.append(&mut vec![
+            Span::raw(" | "),
+            Span::styled(
+                format!("Sleep {}", format_time(sleep, false)),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]);
+    }
+
+    if let Some(count) = app.count {
+        center.append(&mut vec![
+            Span::raw(" | "),
+            Span::styled(
+                count.to_string(),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+        ]);
+    }
+
+    let center = Line::from(center);
 
     let center = Paragraph::new(center)
         .block(Block::default())
@@ -410,66 +1083,381 @@ fn render_status_info_right(
         _ => Color::Indexed(*app.config.color("ShuffleIndicator")),
     };
 
-    let right = Line::from(vec![
+    let (mode, mode_color) = if status.power {
+        (status.playlist_mode.to_string(), mode_color)
+    } else {
+        (
+            "OFF".to_string(),
+            Color::Indexed(*app.config.color("StoppedIndicator"))
+        )
+    };
+
+    let mut right = vec![
         Span::styled(
-            status.playlist_mode.to_string(),
+            mode,
             Style::default()
             .fg(mode_color)
             .add_modifier(Modifier::BOLD)
         ),
+        Span::raw(" | "),
+    ];
+    right.append(&mut volume_spans(status, f.size().width, app));
+    right.append(&mut vec![
         Span::raw(" | ["),
         Span::styled(
-            status.playlist_repeat.to_string(),
-            Style::default()
-            .fg(repeat_color)
-            .add_modifier(Modifier::BOLD)
+            status.playlist_repeat.to_string(),
+            Style::default()
+            .fg(repeat_color)
+            .add_modifier(Modifier::BOLD)
+        ),
+        Span::styled(
+            status.playlist_shuffle.to_string(),
+            Style::default()
+            .fg(shuffle_color)
+            .add_modifier(Modifier::BOLD)
+        ),
+        Span::raw("]"),
+    ]);
+
+    let right = Paragraph::new(Line::from(right))
+        .block(Block::default())
+        .alignment(Alignment::Right);
+
+    f.render_widget(right, chunk);
+}
+
+fn volume_spans<'a>(
+    status: &LmsStatus,
+    width: u16,
+    app: &App
+) -> Vec<Span<'a>> {
+    if status.muted {
+        return vec![
+            Span::styled(
+                "MUTED",
+                Style::default()
+                .fg(Color::Indexed(*app.config.color("MutedIndicator")))
+                .add_modifier(Modifier::BOLD)
+            ),
+        ];
+    }
+
+    let percentage = format!("{:3}%", status.volume);
+    let gauge_color = Color::Indexed(*app.config.color("VolumeGauge"));
+
+    // Only draw the mini gauge when there is room for it
+    if width > 70 {
+        let gauge_width = 10;
+        let filled = (status.volume as usize * gauge_width + 50) / 100;
+        let filled = std::cmp::min(filled, gauge_width);
+
+        vec![
+            Span::styled(
+                line::THICK_HORIZONTAL.repeat(filled),
+                Style::default().fg(gauge_color)
+            ),
+            Span::styled(
+                line::HORIZONTAL.repeat(gauge_width - filled),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+            Span::styled(
+                percentage,
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]
+    } else {
+        vec![
+            Span::styled(
+                percentage,
+                Style::default()
+                .fg(gauge_color)
+                .add_modifier(Modifier::BOLD)
+            ),
+        ]
+    }
+}
+
+fn render_status_bar(f: &mut Frame, chunk: Rect) {
+    let bar = construct_bar(chunk.width);
+
+    let bar = vec![
+        // Empty line for status info
+        Line::from(vec![
+            Span::raw(""),
+        ]),
+        Line::from(vec![
+            Span::raw(bar),
+        ]),
+    ];
+
+    let bar = Paragraph::new(bar)
+        .block(Block::default());
+
+    f.render_widget(bar, chunk);
+}
+
+fn render_playlist(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &mut App
+) {
+    app.playlist_page_size = chunk.height as usize;
+
+    if let Some(playlist) = &app.playlist {
+        let playing_index = app.status
+            .as_ref()
+            .filter(|s| s.total_tracks != 0)
+            .map(|s| s.playlist_index);
+
+        let items: Vec<ListItem> = playlist
+            .tracks
+            .iter()
+            .map(|i| {
+                let now_playing = Some(i.index) == playing_index;
+                ListItem::new(track_span(i, chunk.width, now_playing, app))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default())
+            .highlight_style(
+                Style::default()
+                .add_modifier(Modifier::REVERSED)
+            );
+
+        f.render_stateful_widget(list, chunk, &mut app.playlist_state);
+    }
+}
+
+fn render_browser_state(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(2),
+            ]
+            .as_ref()
+        )
+        .split(f.size());
+
+    render_browser_header(f, chunks[0], app);
+    render_browser_list(f, chunks[1], app);
+    render_browser_footer(f, chunks[2], app);
+}
+
+fn render_alarms_state(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(2),
+            ]
+            .as_ref()
+        )
+        .split(f.size());
+
+    render_alarms_header(f, chunks[0], app);
+    render_alarms_list(f, chunks[1], app);
+    render_alarms_footer(f, chunks[2], app);
+}
+
+fn render_alarms_header(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &App
+) {
+    let name = app.player
+        .as_ref()
+        .map(|player| player.name.clone())
+        .unwrap_or_default();
+
+    let left = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "Alarms: ",
+            Style::default().add_modifier(Modifier::BOLD)
+        ),
+        Span::styled(
+            name,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayerName")))
+        ),
+    ]))
+    .block(Block::default());
+
+    let (enabled, enabled_color) = if app.alarms_enabled {
+        ("ON", *app.config.color("PlayingIndicator"))
+    } else {
+        ("OFF", *app.config.color("StoppedIndicator"))
+    };
+    let right = Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!("{} Alarms | ", app.alarms.len()),
+            Style::default().add_modifier(Modifier::BOLD)
         ),
         Span::styled(
-            status.playlist_shuffle.to_string(),
+            enabled,
             Style::default()
-            .fg(shuffle_color)
+            .fg(Color::Indexed(enabled_color))
             .add_modifier(Modifier::BOLD)
         ),
-        Span::raw("]"),
-    ]);
+    ]))
+    .block(Block::default())
+    .alignment(Alignment::Right);
 
-    let right = Paragraph::new(right)
+    f.render_widget(left, chunk);
+    f.render_widget(right, chunk);
+    render_status_bar(f, chunk);
+}
+
+fn render_alarms_list(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &mut App
+) {
+    if app.alarms.is_empty() {
+        let info = Paragraph::new(
+            raw_para!("", "No alarms are set up for this player.")
+        )
         .block(Block::default())
-        .alignment(Alignment::Right);
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center);
 
-    f.render_widget(right, chunk);
+        f.render_widget(info, chunk);
+        return;
+    }
+
+    let items: Vec<ListItem> = app.alarms
+        .iter()
+        .map(|alarm| {
+            ListItem::new(alarm_span(alarm, chunk.width, app))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default())
+        .highlight_style(
+            Style::default()
+            .add_modifier(Modifier::REVERSED)
+        );
+
+    f.render_stateful_widget(list, chunk, &mut app.alarms_state);
 }
 
-fn render_status_bar(f: &mut Frame, chunk: Rect) {
-    let bar = construct_bar(chunk.width);
+fn render_alarms_footer(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &App
+) {
+    let mut hints = vec!["Esc: back"];
+    if !app.config.read_only {
+        hints.append(&mut vec![
+            "n: new",
+            "e: edit",
+            "d: delete",
+            "Space: enable",
+            "E: all alarms on/off",
+        ]);
+    }
 
-    let bar = vec![
-        // Empty line for status info
+    let footer = vec![
         Line::from(vec![
-            Span::raw(""),
+            Span::raw(construct_bar(chunk.width)),
         ]),
         Line::from(vec![
-            Span::raw(bar),
+            Span::styled(
+                hints.join(" | "),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
         ]),
     ];
 
-    let bar = Paragraph::new(bar)
+    let footer = Paragraph::new(footer)
         .block(Block::default());
 
-    f.render_widget(bar, chunk);
+    f.render_widget(footer, chunk);
 }
 
-fn render_playlist(
+fn render_browser_header(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &App
+) {
+    let mut count = String::new();
+    if let Some(level) = app.browser.current() {
+        count = match level.state.selected() {
+            Some(index) => format!("{}/{}", index + 1, level.total),
+            None => format!("{} Items", level.total),
+        };
+    }
+
+    // Drop the oldest breadcrumbs first when space runs out
+    let max_length = (chunk.width as usize)
+        .saturating_sub(count.len() + 2);
+    let mut breadcrumbs = app.browser.breadcrumbs().join(" > ");
+    while breadcrumbs.chars().count() > max_length {
+        match breadcrumbs.find(" > ") {
+            Some(pos) => {
+                breadcrumbs = format!("…{}", &breadcrumbs[pos + 2..]);
+            },
+            None => {
+                let (truncated, _) = breadcrumbs
+                    .unicode_truncate(max_length.saturating_sub(3));
+                breadcrumbs = format!("{}...", truncated);
+                break;
+            },
+        }
+    }
+
+    let left = Paragraph::new(Line::from(vec![
+        Span::styled(
+            breadcrumbs,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayerName")))
+            .add_modifier(Modifier::BOLD)
+        ),
+    ]))
+    .block(Block::default());
+
+    let right = Paragraph::new(Line::from(vec![
+        Span::styled(
+            count,
+            Style::default().add_modifier(Modifier::BOLD)
+        ),
+    ]))
+    .block(Block::default())
+    .alignment(Alignment::Right);
+
+    f.render_widget(left, chunk);
+    f.render_widget(right, chunk);
+    render_status_bar(f, chunk);
+}
+
+fn render_browser_list(
     f: &mut Frame,
     chunk: Rect,
     app: &mut App
 ) {
-    if let Some(playlist) = &app.playlist {
-        let items: Vec<ListItem> = playlist
-            .tracks
+    app.browser_page_size = chunk.height as usize;
+
+    if let Some(level) = app.browser.levels.last_mut() {
+        if level.loaded && level.items.is_empty() {
+            let info = Paragraph::new(raw_para!("", "Nothing to show here."))
+                .block(Block::default())
+                .style(Style::default().add_modifier(Modifier::BOLD))
+                .alignment(Alignment::Center);
+
+            f.render_widget(info, chunk);
+            return;
+        }
+
+        let items: Vec<ListItem> = level.items
             .iter()
             .map(|i| {
-                ListItem::new(track_span(i, chunk.width, app))
+                ListItem::new(browse_item_span(i, chunk.width, &app.config))
             })
             .collect();
 
@@ -480,15 +1468,64 @@ fn render_playlist(
                 .add_modifier(Modifier::REVERSED)
             );
 
-        f.render_stateful_widget(list, chunk, &mut app.playlist_state);
+        f.render_stateful_widget(list, chunk, &mut level.state);
     }
 }
 
-fn render_playbar_footer(
+fn render_browser_footer(
     f: &mut Frame,
     chunk: Rect,
     app: &App
 ) {
+    let info = if app.search.editing {
+        Line::from(vec![
+            Span::styled(
+                "Search: ",
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+            Span::raw(format!("{}_", app.search.text)),
+        ])
+    } else {
+        let mut hints = vec!["Enter: open", "Backspace: back", "/: search"];
+        if !app.config.read_only {
+            hints.append(&mut vec!["P: play", "a: add", "n: play next"]);
+            match app.state {
+                AppState::SavedPlaylists => {
+                    hints.append(&mut vec!["w: save queue", "d: delete"]);
+                },
+                AppState::Favorites => hints.push("d: remove"),
+                _ => {},
+            }
+        }
+
+        Line::from(vec![
+            Span::styled(
+                hints.join(" | "),
+                Style::default().add_modifier(Modifier::DIM)
+            ),
+        ])
+    };
+
+    let footer = vec![
+        Line::from(vec![
+            Span::raw(construct_bar(chunk.width)),
+        ]),
+        info,
+    ];
+
+    let footer = Paragraph::new(footer)
+        .block(Block::default());
+
+    f.render_widget(footer, chunk);
+}
+
+fn render_playbar_footer(
+    f: &mut Frame,
+    chunk: Rect,
+    app: &App
+) -> Option<Rect> {
+    let mut playbar_area = None;
+
     if let Some(playlist) = &app.playlist {
         if let Some(status) = &app.status {
             let chunks = Layout::default()
@@ -515,16 +1552,13 @@ fn render_playbar_footer(
             f.render_widget(bar, chunks[2]);
 
             let index = status.playlist_index as usize;
-            let current_track: LmsSong;
-
-            if status.total_tracks != 0 {
-                current_track = playlist.tracks[index].clone();
-            } else {
-                current_track = LmsSong::default();
-            }
+            let current_track = playlist.tracks
+                .get(index)
+                .cloned()
+                .unwrap_or_else(LmsSong::default);
             let elapsed = status.elapsed_duration;
 
-            render_playbar_gauge(
+            playbar_area = render_playbar_gauge(
                 f,
                 chunks[1],
                 current_track.clone(),
@@ -541,6 +1575,8 @@ fn render_playbar_footer(
             );
         }
     }
+
+    playbar_area
 }
 
 fn render_playbar_gauge(
@@ -549,7 +1585,7 @@ fn render_playbar_gauge(
     current_track: LmsSong,
     elapsed: f64,
     app: &App
-) {
+) -> Option<Rect> {
     let playbar_chunk = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -568,10 +1604,22 @@ fn render_playbar_gauge(
             Style::default()
             .fg(Color::Indexed(*app.config.color("PlaybarGauge")))
         )
-        .ratio(elapsed / current_track.duration)
+        .ratio(playbar_ratio(elapsed, current_track.duration))
         .label("");
 
     f.render_widget(playbar, playbar_chunk);
+
+    Some(playbar_chunk)
+}
+
+fn playbar_ratio(elapsed: f64, duration: f64) -> f64 {
+    // Streams have no duration, and the elapsed time can briefly run past
+    // the end of a track, so keep the gauge within its bounds
+    if duration > 0.0 {
+        (elapsed / duration).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
 }
 
 fn render_now_playing_info(
@@ -626,7 +1674,7 @@ fn render_now_playing_info_left(
         }
         let left = Line::from(vec![
             Span::styled(
-                format!("Now Playing: "),
+                "Now Playing: ".to_string(),
                 Style::default().add_modifier(Modifier::BOLD)
             ),
             Span::raw(now_playing),
@@ -673,23 +1721,19 @@ fn render_empty_line(f: &mut Frame, chunk: Rect) {
 fn format_time(duration: f64, full_width: bool) -> String {
     let seconds = duration as u64 % 60;
     let minutes = duration as u64 / 60;
-    let time_str: String;
-
     if minutes >= 60 {
         let hours = minutes / 60;
         let minutes = minutes % 60;
-        time_str = match full_width {
+        match full_width {
             true => format!("{:2}:{:02}:{:02}", hours, minutes, seconds),
             false => format!("{}:{:02}:{:02}", hours, minutes, seconds),
-        };
+        }
     } else {
-        time_str = match full_width {
+        match full_width {
             true => format!("{:2}:{:02}", minutes, seconds),
             false => format!("{}:{:02}", minutes, seconds),
-        };
+        }
     }
-
-    time_str
 }
 
 fn construct_bar(length: u16) -> String {
@@ -705,11 +1749,12 @@ fn construct_bar(length: u16) -> String {
 fn track_span<'a>(
     track: &'a LmsSong,
     width: u16,
+    now_playing: bool,
     app: &App
 ) -> Line<'a> {
     let index = format!("{:2}", track.index + 1);
-    let index_spaces = " ";
-    let mut current_width = index.len() + index_spaces.len();
+    let index_marker = if now_playing { "▶" } else { " " };
+    let mut current_width = index.len() + 1;
 
     let artist_width_limit = 50;
     let album_width_limit = 80;
@@ -753,12 +1798,17 @@ fn track_span<'a>(
             current_width
         );
 
-    Line::from(vec![
+    let line = Line::from(vec![
         Span::styled(
-            format!("{}{}", index, index_spaces),
+            index,
             Style::default()
             .fg(Color::Indexed(*app.config.color("TrackIndex")))
         ),
+        Span::styled(
+            index_marker,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayingIndicator")))
+        ),
         Span::styled(
             format!("{}{}", title, title_spaces),
             Style::default()
@@ -779,7 +1829,272 @@ fn track_span<'a>(
             Style::default()
             .fg(Color::Indexed(*app.config.color("TrackDuration")))
         ),
-    ])
+    ]);
+
+    if now_playing {
+        line.patch_style(Style::default().add_modifier(Modifier::BOLD))
+    } else {
+        line
+    }
+}
+
+fn alarm_span<'a>(
+    alarm: &'a LmsAlarm,
+    width: u16,
+    app: &App
+) -> Line<'a> {
+    let (marker, marker_color) = if alarm.enabled {
+        ("● ", *app.config.color("PlayingIndicator"))
+    } else {
+        ("○ ", *app.config.color("StoppedIndicator"))
+    };
+    let time = format!("{} ", format_alarm_time(alarm.time));
+    let mut current_width = 2 + time.len();
+
+    let days_width = 30;
+    let playlist_width_limit = 60;
+
+    let (days, days_spaces);
+    (days, days_spaces, current_width) =
+        construct_text_column(
+            describe_days(&alarm.days()),
+            std::cmp::min(days_width, (width as usize / 2).max(2)),
+            current_width
+        );
+
+    let repeat = if alarm.repeat { "Repeat " } else { "Once   " };
+    let volume = format!("{:3}%", alarm.volume);
+    current_width += repeat.len() + volume.len();
+
+    let mut playlist = String::new();
+    if width > playlist_width_limit {
+        let url = if alarm.plays_current_playlist() {
+            "Current playlist".to_string()
+        } else {
+            alarm.url.clone()
+        };
+        (playlist, _, _) =
+            construct_text_column(
+                url,
+                (width as usize).saturating_sub(current_width).max(4) - 1,
+                current_width
+            );
+        playlist = format!("  {}", playlist);
+    }
+
+    let line = Line::from(vec![
+        Span::styled(
+            marker,
+            Style::default().fg(Color::Indexed(marker_color))
+        ),
+        Span::styled(
+            time,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("TrackTitle")))
+            .add_modifier(Modifier::BOLD)
+        ),
+        Span::styled(
+            format!("{}{}", days, days_spaces),
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("TrackArtist")))
+        ),
+        Span::styled(
+            repeat,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("RepeatIndicator")))
+        ),
+        Span::styled(
+            volume,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("VolumeGauge")))
+        ),
+        Span::styled(
+            playlist,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("TrackAlbum")))
+        ),
+    ]);
+
+    if alarm.enabled {
+        line
+    } else {
+        line.patch_style(Style::default().add_modifier(Modifier::DIM))
+    }
+}
+
+fn player_span<'a>(
+    player: &'a LmsPlayer,
+    width: u16,
+    app: &App
+) -> Line<'a> {
+    let marker = if player.isplaying { "▶ " } else { "  " };
+    let mut current_width = 2;
+
+    let model_width_limit = 40;
+    let address_width_limit = 60;
+    let firmware_width_limit = 80;
+    let mut width_factor = width as usize / 3;
+    if width > firmware_width_limit {
+        width_factor = width as usize / 5;
+    } else if width > address_width_limit {
+        width_factor = width as usize / 4;
+    }
+
+    let mut model = String::new();
+    let mut model_spaces = String::new();
+    if width > model_width_limit {
+        (model, model_spaces, current_width) =
+            construct_text_column(
+                player.model.clone(),
+                width_factor,
+                current_width
+            );
+    }
+
+    let mut address = String::new();
+    let mut address_spaces = String::new();
+    if width > address_width_limit {
+        (address, address_spaces, current_width) =
+            construct_text_column(
+                player.address().to_string(),
+                width_factor,
+                current_width
+            );
+    }
+
+    let mut firmware = String::new();
+    let mut firmware_spaces = String::new();
+    if width > firmware_width_limit {
+        (firmware, firmware_spaces, current_width) =
+            construct_text_column(
+                player.firmware.clone(),
+                width_factor,
+                current_width
+            );
+    }
+
+    let (power, power_color) = if !player.connected {
+        ("OFFLINE", *app.config.color("StoppedIndicator"))
+    } else if !player.is_powered() {
+        ("OFF", *app.config.color("StoppedIndicator"))
+    } else {
+        ("ON", *app.config.color("PlayingIndicator"))
+    };
+    let power = format!("{:>8}", power);
+    current_width += power.len();
+
+    let group = app.player_list
+        .group_number(&player.playerid)
+        .map(|number| format!("Group {}", number))
+        .unwrap_or_default();
+    let group = format!("{:>9}", group);
+    current_width += group.len();
+
+    let (name, name_spaces);
+    (name, name_spaces, _) =
+        construct_text_column(
+            player.name.clone(),
+            std::cmp::max(
+                (width as usize).saturating_sub(current_width) + 1,
+                2
+            ),
+            current_width
+        );
+
+    let line = Line::from(vec![
+        Span::styled(
+            marker,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayingIndicator")))
+        ),
+        Span::styled(
+            format!("{}{}", name, name_spaces),
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayerName")))
+        ),
+        Span::raw(format!("{}{}", model, model_spaces)),
+        Span::raw(format!("{}{}", address, address_spaces)),
+        Span::raw(format!("{}{}", firmware, firmware_spaces)),
+        Span::styled(
+            group,
+            Style::default()
+            .fg(Color::Indexed(*app.config.color("PlayerName")))
+        ),
+        Span::styled(
+            power,
+            Style::default()
+            .fg(Color::Indexed(power_color))
+            .add_modifier(Modifier::BOLD)
+        ),
+    ]);
+
+    if player.is_available() {
+        line
+    } else {
+        line.patch_style(Style::default().add_modifier(Modifier::DIM))
+    }
+}
+
+fn browse_item_span<'a>(
+    item: &'a BrowseItem,
+    width: u16,
+    config: &Config
+) -> Line<'a> {
+    if item.header {
+        return Line::from(vec![
+            Span::styled(
+                item.name.clone(),
+                Style::default()
+                .fg(Color::Indexed(*config.color("PlayerName")))
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            ),
+        ]);
+    }
+
+    let marker = if item.browse.is_some() { "▸ " } else { "  " };
+    let current_width = 2;
+
+    let mut spans = vec![
+        Span::styled(
+            marker,
+            Style::default()
+            .fg(Color::Indexed(*config.color("TrackIndex")))
+        ),
+    ];
+
+    match &item.detail {
+        Some(detail) if width > 40 => {
+            let name_width = (width as usize - current_width) * 2 / 3;
+            let (name, name_spaces, current_width) =
+                construct_text_column(
+                    item.name.clone(),
+                    name_width,
+                    current_width
+                );
+            let (detail, _) = detail
+                .unicode_truncate(width as usize - current_width);
+
+            spans.push(Span::styled(
+                format!("{}{}", name, name_spaces),
+                Style::default()
+                .fg(Color::Indexed(*config.color("TrackTitle")))
+            ));
+            spans.push(Span::styled(
+                detail.to_string(),
+                Style::default()
+                .fg(Color::Indexed(*config.color("TrackArtist")))
+            ));
+        },
+        _ => {
+            spans.push(Span::styled(
+                item.name.clone(),
+                Style::default()
+                .fg(Color::Indexed(*config.color("TrackTitle")))
+            ));
+        },
+    }
+
+    Line::from(spans)
 }
 
 fn construct_text_column(
@@ -802,7 +2117,7 @@ fn construct_text_column(
         1
     );
     for _ in 0..num_spaces {
-        spaces.push_str(" ");
+        spaces.push(' ');
     }
     text = base_text.clone();
     let (text_str, new_width) = text